@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// A fictional versioning CLI
 #[derive(Debug, Parser)]
@@ -35,6 +35,24 @@ enum Commands {
     #[clap(arg_required_else_help = true)]
     Harness {
     },
+    /// Print a rust source file with syntax highlighting
+    #[clap(arg_required_else_help = true)]
+    Highlight {
+        /// The path to the rust file
+        #[clap(value_parser)]
+        file: String,
+        /// How to render the highlighting
+        #[clap(long, value_enum, default_value_t = HighlightFormat::Ansi)]
+        format: HighlightFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum HighlightFormat {
+    /// Colors for the terminal
+    Ansi,
+    /// A self-contained HTML snippet
+    Html,
 }
 
 
@@ -50,6 +68,14 @@ fn main() {
                 crate_path.unwrap_or(std::env::current_dir().unwrap().display().to_string())
             ).unwrap();
         },
+        Commands::Highlight { file, format } => {
+            let source = std::fs::read_to_string(&file).unwrap();
+            let lexer = rust_lexer::Lexer::new(&source);
+            match format {
+                HighlightFormat::Ansi => print!("{}", rust_lexer::highlight_ansi(lexer)),
+                HighlightFormat::Html => print!("{}", rust_lexer::highlight_html(lexer)),
+            }
+        },
         Commands::Harness {..} => unimplemented!(),
         Commands::BindGen {..} => unimplemented!(),
     }
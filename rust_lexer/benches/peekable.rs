@@ -0,0 +1,170 @@
+//! Compare the token cursor against the fixed size ring buffer it replaced
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_lexer::{Lexer, Peekable, Token};
+
+/// The previous design, kept as a baseline. The buffer is initialized with
+/// `from_fn` because the original `MaybeUninit` trick is UB for tokens, and
+/// the bounds of `consume`, `push` and `get` are fixed so that it returns the
+/// right tokens.
+mod ring_buffer {
+    pub struct RingBuffer<T: Default + Clone, const BUFFER_SIZE: usize> {
+        buffer: [T; BUFFER_SIZE],
+        start: usize,
+        end: usize,
+    }
+
+    impl<const BUFFER_SIZE: usize, T: Default + Clone> RingBuffer<T, BUFFER_SIZE> {
+        pub fn new() -> Self {
+            Self {
+                buffer: core::array::from_fn(|_| T::default()),
+                start: 0,
+                end: 0,
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        pub fn len(&self) -> usize {
+            ((BUFFER_SIZE + self.end) - self.start) % BUFFER_SIZE
+        }
+
+        pub fn pop(&mut self) -> Option<T> {
+            if self.is_empty() {
+                return None;
+            }
+            let result = &self.buffer[self.start];
+            self.start = (BUFFER_SIZE + self.start + 1) % BUFFER_SIZE;
+            Some(result.clone())
+        }
+
+        pub fn consume(&mut self, quantity: usize) {
+            let quantity = quantity.min(self.len());
+            self.start = (BUFFER_SIZE + self.start + quantity) % BUFFER_SIZE;
+        }
+
+        pub fn push(&mut self, value: T) -> Option<()> {
+            if self.len() + 1 == BUFFER_SIZE {
+                return None;
+            }
+            self.buffer[self.end] = value;
+            self.end = (BUFFER_SIZE + self.end + 1) % BUFFER_SIZE;
+            Some(())
+        }
+
+        pub fn get(&mut self, index: usize) -> Option<T> {
+            if index >= self.len() {
+                return None;
+            }
+            let idx = (BUFFER_SIZE + self.start + index) % BUFFER_SIZE;
+            Some(self.buffer[idx].clone())
+        }
+    }
+
+    pub struct Peekable<T: Default + Clone, I: Iterator<Item=T>, const BUFFER_SIZE: usize> {
+        iterator: I,
+        cache: RingBuffer<T, BUFFER_SIZE>,
+    }
+
+    impl<T: Default + Clone, I: Iterator<Item=T>, const BUFFER_SIZE: usize> Peekable<T, I, BUFFER_SIZE> {
+        pub fn new(iterator: I) -> Self {
+            Peekable {
+                iterator,
+                cache: RingBuffer::new(),
+            }
+        }
+
+        pub fn get(&mut self, index: usize) -> Option<T> {
+            while self.cache.len() <= index {
+                self.cache.push(self.iterator.next()?).unwrap();
+            }
+            self.cache.get(index)
+        }
+
+        pub fn consume(&mut self, quantity: usize) -> Option<()> {
+            let reminder = quantity.saturating_sub(self.cache.len());
+            self.cache.consume(quantity - reminder);
+            for _ in 0..reminder {
+                self.iterator.next()?;
+            }
+            Some(())
+        }
+    }
+
+    impl<T: Default + Clone, I: Iterator<Item=T>, const BUFFER_SIZE: usize> Iterator for Peekable<T, I, BUFFER_SIZE> {
+        type Item = T;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.cache.is_empty() {
+                self.iterator.next()
+            } else {
+                self.cache.pop()
+            }
+        }
+    }
+}
+
+/// A large file made of the sources of the lexer
+fn large_file() -> String {
+    [
+        include_str!("../src/chars_constants.rs"),
+        include_str!("../src/lib.rs"),
+        include_str!("../src/literal.rs"),
+        include_str!("../src/escape.rs"),
+        include_str!("../src/peekable.rs"),
+    ].concat()
+}
+
+fn bench_peekable(c: &mut Criterion) {
+    let source = large_file();
+    let tokens = Lexer::new(&source).collect::<Vec<Token>>();
+
+    let mut group = c.benchmark_group("lookahead_2");
+    group.bench_function("ring_buffer", |b| b.iter(|| {
+        let mut stream = ring_buffer::Peekable::<_, _, 4096>::new(tokens.iter().cloned());
+        while let Some(token) = stream.get(0) {
+            black_box((token, stream.get(1)));
+            stream.consume(1);
+        }
+    }));
+    group.bench_function("cursor", |b| b.iter(|| {
+        let mut stream = Peekable::new(tokens.iter().cloned());
+        while let Some(token) = stream.get(0) {
+            black_box((token, stream.get(1)));
+            stream.consume(1);
+        }
+    }));
+    group.finish();
+
+    let mut group = c.benchmark_group("next");
+    group.bench_function("ring_buffer", |b| b.iter(|| {
+        let stream = ring_buffer::Peekable::<_, _, 4096>::new(tokens.iter().cloned());
+        black_box(stream.count())
+    }));
+    group.bench_function("cursor", |b| b.iter(|| {
+        let stream = Peekable::new(tokens.iter().cloned());
+        black_box(stream.count())
+    }));
+    group.finish();
+
+    let mut group = c.benchmark_group("backtracking");
+    group.bench_function("cursor", |b| b.iter(|| {
+        let mut stream = Peekable::new(tokens.iter().cloned());
+        loop {
+            let checkpoint = stream.checkpoint();
+            if stream.consume(16).is_none() {
+                stream.commit(checkpoint);
+                break;
+            }
+            stream.rewind(checkpoint);
+            if stream.next().is_none() {
+                break;
+            }
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, bench_peekable);
+criterion_main!(benches);
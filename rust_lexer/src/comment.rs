@@ -14,29 +14,26 @@ impl<'a> Comment<'a> {
     pub fn len(&self) -> usize {
         use Comment::*;
         match self {
-            LineComment(x)   => x.len() + 2 + 1,
+            // the newline ending a line comment is not part of it
+            LineComment(x)   => x.len() + 2,
             BlockComment(x)  => x.len() + 2 + 2,
-            InnerLineDoc(x)  => x.len() + 3 + 1,
-            InnerBlockDoc(x) => x.len() + 2 + 2,
-            OuterLineDoc(x)  => x.len() + 3 + 1,
+            InnerLineDoc(x)  => x.len() + 3,
+            InnerBlockDoc(x) => x.len() + 3 + 2,
+            OuterLineDoc(x)  => x.len() + 3,
             OuterBlockDoc(x) => x.len() + 3 + 2,
         }
     }
+
+    /// If it's a doc comment, which is an attribute and not trivia
+    pub fn is_doc(&self) -> bool {
+        !matches!(self, Comment::LineComment(_) | Comment::BlockComment(_))
+    }
 }
 
 fn find_matching(data: &str) -> Result<usize, ()> {
     let mut index = 0;
     loop {
-        if data[index..].starts_with("/**") {
-            index += find_matching(&data[index + 3..])? + 3 + 2;
-            continue;
-        }
-
-        if data[index..].starts_with("/*!") {
-            index += find_matching(&data[index + 3..])? + 3 + 2;
-            continue;
-        }
-
+        // the doc comments nest as plain ones, `/**` and `/*!` start with `/*`
         if data[index..].starts_with("/*") {
             index += find_matching(&data[index + 2..])? + 2 + 2;
             continue;
@@ -51,10 +48,26 @@ fn find_matching(data: &str) -> Result<usize, ()> {
     }
 }
 
+/// Render the comment as it was in the source
+impl<'a> core::fmt::Display for Comment<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use Comment::*;
+        match self {
+            LineComment(x)   => write!(f, "//{}", x),
+            BlockComment(x)  => write!(f, "/*{}*/", x),
+            InnerLineDoc(x)  => write!(f, "//!{}", x),
+            InnerBlockDoc(x) => write!(f, "/*!{}*/", x),
+            OuterLineDoc(x)  => write!(f, "///{}", x),
+            OuterBlockDoc(x) => write!(f, "/**{}*/", x),
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a str> for Comment<'a> {
     type Error = ();
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        if value.starts_with("/**") {
+        // `/**/` and `/***` are plain comments
+        if value.starts_with("/**") && !value.starts_with("/**/") && !value.starts_with("/***") {
             let res_index = find_matching(&value[3..])?;
             return Ok(Comment::OuterBlockDoc(&value[3..3 + res_index]));
         }
@@ -69,7 +82,8 @@ impl<'a> TryFrom<&'a str> for Comment<'a> {
             return Ok(Comment::BlockComment(&value[2..2 + res_index]));
         }
 
-        if value.starts_with("///") {
+        // `////` is a plain comment
+        if value.starts_with("///") && !value.starts_with("////") {
             let (comment, _) = value.split_once("\n").unwrap_or((value, ""));
             return Ok(Comment::OuterLineDoc(&comment[3..]));
         }
@@ -106,5 +120,11 @@ mod test_commnet {
         assert_eq!(Comment::try_from("/* /*! /** /* */ */ */ */a // less").unwrap(), Comment::BlockComment(" /*! /** /* */ */ */ "));
         assert_eq!(Comment::try_from("/** /*! /** /* */ */ */ */a // less").unwrap(), Comment::OuterBlockDoc(" /*! /** /* */ */ */ "));
         assert_eq!(Comment::try_from("/*! /*! /** /* */ */ */ */a // less").unwrap(), Comment::InnerBlockDoc(" /*! /** /* */ */ */ "));
+
+        assert_eq!(Comment::try_from("/**/b").unwrap(), Comment::BlockComment(""));
+        assert_eq!(Comment::try_from("/***/b").unwrap(), Comment::BlockComment("*"));
+        assert_eq!(Comment::try_from("/*** a */b").unwrap(), Comment::BlockComment("** a "));
+        assert_eq!(Comment::try_from("/* /**/ */b").unwrap(), Comment::BlockComment(" /**/ "));
+        assert_eq!(Comment::try_from("//// a\nb").unwrap(), Comment::LineComment("// a"));
     }
 }
\ No newline at end of file
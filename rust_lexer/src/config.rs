@@ -0,0 +1,30 @@
+/// The Rust editions, which change what words are keywords
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Edition {
+    Edition2015,
+    Edition2018,
+    #[default]
+    Edition2021,
+    Edition2024,
+}
+
+impl TryFrom<&str> for Edition {
+    type Error = ();
+    /// Parse the edition as written in a `Cargo.toml`, e.g. `"2018"`
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        use Edition::*;
+        Ok(match value {
+            "2015" => Edition2015,
+            "2018" => Edition2018,
+            "2021" => Edition2021,
+            "2024" => Edition2024,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Settings of a [`crate::Lexer`] that change how the source is tokenized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LexerConfig {
+    pub edition: Edition,
+}
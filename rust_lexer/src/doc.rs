@@ -0,0 +1,220 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::{Comment, Identifier, Literal, LiteralValue, Span, SpannedToken, Symbol, Token};
+
+/// The tokens of the attribute a doc comment stands for, e.g. `/// a` is
+/// `#[doc = r" a"]` and `//! a` is `#![doc = r" a"]`. All of them get the
+/// span of the comment. Returns `None` if it is not a doc comment.
+pub fn doc_attribute<'a>(comment: &Comment<'a>, span: Span) -> Option<Vec<SpannedToken<'a>>> {
+    let (text, is_inner) = match comment {
+        Comment::InnerLineDoc(text) | Comment::InnerBlockDoc(text) => (*text, true),
+        Comment::OuterLineDoc(text) | Comment::OuterBlockDoc(text) => (*text, false),
+        _ => return None,
+    };
+    let start = if is_inner { Symbol::Shebang } else { Symbol::Pound };
+    let tokens = [
+        Token::Symbol(start),
+        Token::Symbol(Symbol::OpenBraket),
+        Token::Identifier(Identifier("doc")),
+        Token::Symbol(Symbol::Eq),
+        Token::Literal(Literal::RawString(text, raw_hashes(text))),
+        Token::Symbol(Symbol::CloseBraket),
+    ];
+    Some(tokens.into_iter().map(|token| SpannedToken { token, span }).collect())
+}
+
+/// How many `#` a raw string needs to hold the text
+fn raw_hashes(text: &str) -> usize {
+    text.split('"')
+        .skip(1)
+        .map(|after_quote| after_quote.len() - after_quote.trim_start_matches('#').len() + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Replace the doc comments of a token stream with the equivalent doc
+/// attributes, so that consumers only have to handle `#[doc = ...]`
+pub struct DesugarDocs<'a, I: Iterator<Item=SpannedToken<'a>>> {
+    iterator: I,
+    pending: VecDeque<SpannedToken<'a>>,
+}
+
+impl<'a, I: Iterator<Item=SpannedToken<'a>>> DesugarDocs<'a, I> {
+    pub fn new(iterator: I) -> Self {
+        DesugarDocs {
+            iterator,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item=SpannedToken<'a>>> Iterator for DesugarDocs<'a, I> {
+    type Item = SpannedToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.pending.pop_front() {
+            return Some(token);
+        }
+        let token = self.iterator.next()?;
+        match &token.token {
+            Token::Comment(comment) => match doc_attribute(comment, token.span) {
+                Some(attribute) => {
+                    self.pending.extend(attribute);
+                    self.pending.pop_front()
+                },
+                None => Some(token),
+            },
+            _ => Some(token),
+        }
+    }
+}
+
+/// The documentation given by a run of consecutive doc comments or doc
+/// attributes of the same kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Doc {
+    /// If it documents the enclosing item, as `//!` and `#![doc]`
+    pub is_inner: bool,
+    /// The merged lines without their common indentation
+    pub text: String,
+    pub span: Span,
+}
+
+/// Find the docs in the tokens, both as comments and as `#[doc = "..."]`
+/// attributes with a string literal, merging the consecutive ones like
+/// rustdoc does. Non-doc comments don't break a run, any other token does.
+pub fn collect_docs(tokens: &[SpannedToken<'_>]) -> Vec<Doc> {
+    let mut docs = Vec::new();
+    // the fragments of the current run, if it is inner and its span
+    let mut run: Vec<String> = Vec::new();
+    let mut run_span: Option<(bool, Span)> = None;
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let (fragment, is_inner, len) = match doc_at(&tokens[index..]) {
+            Some(found) => found,
+            None => {
+                if !matches!(&tokens[index].token, Token::Comment(_)) {
+                    flush(&mut docs, &mut run, &mut run_span);
+                }
+                index += 1;
+                continue;
+            },
+        };
+        let span = tokens[index].span.join(&tokens[index + len - 1].span);
+        run_span = match run_span {
+            Some((run_inner, run_start)) if run_inner == is_inner => Some((is_inner, run_start.join(&span))),
+            _ => {
+                flush(&mut docs, &mut run, &mut run_span);
+                Some((is_inner, span))
+            },
+        };
+        run.push(fragment);
+        index += len;
+    }
+    flush(&mut docs, &mut run, &mut run_span);
+    docs
+}
+
+/// The text of the doc comment or doc attribute starting the tokens, if it
+/// is inner and how many tokens it takes
+fn doc_at(tokens: &[SpannedToken<'_>]) -> Option<(String, bool, usize)> {
+    let token = |index: usize| tokens.get(index).map(|token| &token.token);
+    match token(0)? {
+        Token::Comment(Comment::InnerLineDoc(text)) => Some((String::from(*text), true, 1)),
+        Token::Comment(Comment::OuterLineDoc(text)) => Some((String::from(*text), false, 1)),
+        Token::Comment(Comment::InnerBlockDoc(text)) => Some((strip_block_margin(text), true, 1)),
+        Token::Comment(Comment::OuterBlockDoc(text)) => Some((strip_block_margin(text), false, 1)),
+        Token::Symbol(start @ (Symbol::Pound | Symbol::Shebang)) => {
+            let is_doc = token(1) == Some(&Token::Symbol(Symbol::OpenBraket))
+                && matches!(token(2), Some(Token::Identifier(identifier)) if identifier.name() == "doc")
+                && token(3) == Some(&Token::Symbol(Symbol::Eq))
+                && token(5) == Some(&Token::Symbol(Symbol::CloseBraket));
+            if !is_doc {
+                return None;
+            }
+            match token(4)? {
+                Token::Literal(literal) => match literal.value()? {
+                    // the lines of a doc attribute are not indented by a
+                    // space as the ones of a comment, rustdoc adds it back
+                    Ok(LiteralValue::Str(text)) => Some((indent(&text), *start == Symbol::Shebang, 6)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+fn indent(text: &str) -> String {
+    text.split('\n')
+        .map(|line| if line.trim().is_empty() { String::from(line) } else { alloc::format!(" {}", line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Drop the blank first and last lines of a block doc and the ` * ` that
+/// may start all the others
+fn strip_block_margin(text: &str) -> String {
+    let mut lines = text.split('\n').collect::<Vec<_>>();
+    if lines.len() > 1 && lines.first().is_some_and(|line| line.trim().is_empty()) {
+        lines.remove(0);
+    }
+    if lines.len() > 1 && lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let has_margin = lines.iter().all(|line| line.trim_start().starts_with('*'));
+    lines.into_iter()
+        .map(|line| if has_margin { &line.trim_start()[1..] } else { line })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn flush(docs: &mut Vec<Doc>, run: &mut Vec<String>, run_span: &mut Option<(bool, Span)>) {
+    let Some((is_inner, span)) = run_span.take() else {
+        return;
+    };
+    let text = run.join("\n");
+    run.clear();
+    let indentation = text.split('\n')
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let text = text.split('\n')
+        .map(|line| line.get(indentation..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    docs.push(Doc { is_inner, text, span });
+}
+
+#[cfg(test)]
+mod test_doc {
+    use super::*;
+    use crate::Lexer;
+    use alloc::string::ToString;
+    #[test]
+    fn desugar_and_collect() {
+        let source = "//! Crate\n/// Sum `a`\n///\n///     and \"#b\n/** c */\nfn f() {}\n#[doc = \"d\\n  e\"]\n#[doc = \"f\"]\nstruct S;";
+        let desugared = DesugarDocs::new(Lexer::new(source).spanned())
+            .map(|token| token.token.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert!(desugared.starts_with("#! [ doc = r\" Crate\" ] # [ doc = r\" Sum `a`\" ] # [ doc = r\"\" ] # [ doc = r##\"     and \"#b\"## ] # [ doc = r\" c \" ] fn"));
+
+        let tokens = Lexer::new(source).spanned().collect::<Vec<_>>();
+        let docs = collect_docs(&tokens);
+        let texts = docs.iter().map(|doc| (doc.is_inner, doc.text.as_str())).collect::<Vec<_>>();
+        assert_eq!(texts, [
+            (true, "Crate"),
+            (false, "Sum `a`\n\n    and \"#b\nc"),
+            (false, "d\n  e\nf"),
+        ]);
+        assert_eq!(&source[docs[1].span.byte_range()], "/// Sum `a`\n///\n///     and \"#b\n/** c */");
+        // desugaring doesn't change the docs
+        let desugared = DesugarDocs::new(tokens.into_iter()).collect::<Vec<_>>();
+        assert_eq!(collect_docs(&desugared), docs);
+    }
+}
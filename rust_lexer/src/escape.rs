@@ -0,0 +1,329 @@
+//! Decoding of the escape sequences in string, char and byte literals, as
+//! defined here https://doc.rust-lang.org/reference/tokens.html#escapes
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeErrorKind {
+    /// A `\` at the end of the literal
+    LoneSlash,
+    /// An unknown escape such as `\q`
+    InvalidEscape(char),
+    TooShortHexEscape,
+    InvalidCharInHexEscape,
+    /// `\x80` and above are only valid in byte and C string literals
+    OutOfRangeHexEscape,
+    NoBraceInUnicodeEscape,
+    InvalidCharInUnicodeEscape,
+    EmptyUnicodeEscape,
+    UnclosedUnicodeEscape,
+    /// More than 6 hex digits
+    OverlongUnicodeEscape,
+    LoneSurrogateUnicodeEscape,
+    OutOfRangeUnicodeEscape,
+    /// `\u{...}` inside a byte or byte string literal
+    UnicodeEscapeInByte,
+    NonAsciiCharInByte,
+    NulInCStr,
+    BareCarriageReturn,
+    /// A char or byte literal that does not hold exactly one value
+    MoreThanOneChar,
+    ZeroChars,
+}
+
+/// An invalid escape and its byte offset in the content of the literal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscapeError {
+    pub kind: EscapeErrorKind,
+    pub offset: usize,
+}
+
+/// The decoded content of a text literal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiteralValue<'a> {
+    /// Value of chars, strings and raw strings
+    Str(Cow<'a, str>),
+    /// Value of bytes, byte strings and C strings, without the nul terminator
+    Bytes(Cow<'a, [u8]>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Char,
+    Str,
+    Byte,
+    ByteStr,
+    CStr,
+}
+
+impl Mode {
+    fn is_byte(&self) -> bool {
+        matches!(self, Mode::Byte | Mode::ByteStr)
+    }
+
+    fn allows_continuation(&self) -> bool {
+        matches!(self, Mode::Str | Mode::ByteStr | Mode::CStr)
+    }
+}
+
+/// A decoded unit, C strings mix the two
+enum Unit {
+    Char(char),
+    Byte(u8),
+}
+
+fn error<T>(kind: EscapeErrorKind, offset: usize) -> Result<T, EscapeError> {
+    Err(EscapeError { kind, offset })
+}
+
+/// Decode the escape at the start of `escape`, which is what follows the
+/// backslash, returning the unit and how many bytes were used.
+fn unescape_one(escape: &str, mode: Mode, offset: usize) -> Result<(Unit, usize), EscapeError> {
+    use EscapeErrorKind::*;
+    let mut chars = escape.chars();
+    let first = match chars.next() {
+        Some(char) => char,
+        None => return error(LoneSlash, offset),
+    };
+
+    let unit = match first {
+        'n' => Unit::Char('\n'),
+        'r' => Unit::Char('\r'),
+        't' => Unit::Char('\t'),
+        '\\' => Unit::Char('\\'),
+        '0' => Unit::Char('\0'),
+        '\'' => Unit::Char('\''),
+        '"' => Unit::Char('"'),
+        'x' => {
+            let digits = escape.as_bytes().get(1..3).ok_or(EscapeError { kind: TooShortHexEscape, offset })?;
+            // exactly two digits, `from_str_radix` would take `+1`
+            if !digits.iter().all(u8::is_ascii_hexdigit) {
+                return error(InvalidCharInHexEscape, offset);
+            }
+            let value = digits.iter()
+                .filter_map(|digit| char::from(*digit).to_digit(16))
+                .fold(0_u8, |acc, digit| acc * 16 + digit as u8);
+            if value > 0x7F && !matches!(mode, Mode::Byte | Mode::ByteStr | Mode::CStr) {
+                return error(OutOfRangeHexEscape, offset);
+            }
+            return Ok((Unit::Byte(value), 3));
+        },
+        'u' => {
+            if mode.is_byte() {
+                return error(UnicodeEscapeInByte, offset);
+            }
+            let body = escape[1..].strip_prefix('{')
+                .ok_or(EscapeError { kind: NoBraceInUnicodeEscape, offset })?;
+            let close = body.find('}')
+                .ok_or(EscapeError { kind: UnclosedUnicodeEscape, offset })?;
+            let digits = &body[..close];
+            if digits.is_empty() || digits.starts_with('_') {
+                return error(EmptyUnicodeEscape, offset);
+            }
+            if !digits.chars().all(|c| c.is_ascii_hexdigit() || c == '_') {
+                return error(InvalidCharInUnicodeEscape, offset);
+            }
+            let digits_count = digits.chars().filter(|c| *c != '_').count();
+            if digits_count > 6 {
+                return error(OverlongUnicodeEscape, offset);
+            }
+            let value = digits.chars()
+                .filter_map(|c| c.to_digit(16))
+                .fold(0_u32, |acc, digit| acc * 16 + digit);
+            let char = match char::from_u32(value) {
+                Some(char) => char,
+                None if (0xD800..0xE000).contains(&value) => return error(LoneSurrogateUnicodeEscape, offset),
+                None => return error(OutOfRangeUnicodeEscape, offset),
+            };
+            // `u{` + digits + `}`
+            return Ok((Unit::Char(char), 1 + 1 + close + 1));
+        },
+        other => return error(InvalidEscape(other), offset),
+    };
+    Ok((unit, 1))
+}
+
+/// Decode `content` calling `callback` for every unit
+fn unescape(content: &str, mode: Mode, mut callback: impl FnMut(Unit)) -> Result<(), EscapeError> {
+    use EscapeErrorKind::*;
+    let mut index = 0;
+    while let Some(char) = content[index..].chars().next() {
+        let offset = index;
+        match char {
+            '\\' => {
+                let escape = &content[index + 1..];
+                // line continuation, skip the newline and the indentation
+                if (escape.starts_with('\n') || escape.starts_with("\r\n")) && mode.allows_continuation() {
+                    let rest = escape.trim_start_matches([' ', '\t', '\n', '\r']);
+                    index = content.len() - rest.len();
+                    continue;
+                }
+                let (unit, len) = unescape_one(escape, mode, offset)?;
+                if mode == Mode::CStr && matches!(unit, Unit::Char('\0') | Unit::Byte(0)) {
+                    return error(NulInCStr, offset);
+                }
+                callback(unit);
+                index += 1 + len;
+            },
+            // `\r\n` is read as `\n` as rustc does, a lone `\r` is an error
+            '\r' => {
+                if !content[index + 1..].starts_with('\n') {
+                    return error(BareCarriageReturn, offset);
+                }
+                callback(Unit::Char('\n'));
+                index += 2;
+            },
+            _ => {
+                if mode.is_byte() && !char.is_ascii() {
+                    return error(NonAsciiCharInByte, offset);
+                }
+                if mode == Mode::CStr && char == '\0' {
+                    return error(NulInCStr, offset);
+                }
+                callback(Unit::Char(char));
+                index += char.len_utf8();
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Decode the content of a text literal, borrowing it when there is
+/// nothing to unescape.
+pub(crate) fn decode(content: &str, mode: Mode) -> Result<LiteralValue<'_>, EscapeError> {
+    let needs_decoding = content.contains(['\\', '\r', '\0']) || (mode.is_byte() && !content.is_ascii());
+
+    let value = match mode {
+        Mode::Char | Mode::Str => {
+            if needs_decoding {
+                let mut result = String::with_capacity(content.len());
+                unescape(content, mode, |unit| match unit {
+                    Unit::Char(char) => result.push(char),
+                    // only ascii hex escapes are allowed here
+                    Unit::Byte(byte) => result.push(byte as char),
+                })?;
+                LiteralValue::Str(Cow::Owned(result))
+            } else {
+                LiteralValue::Str(Cow::Borrowed(content))
+            }
+        },
+        Mode::Byte | Mode::ByteStr | Mode::CStr => {
+            if needs_decoding {
+                let mut result = Vec::with_capacity(content.len());
+                unescape(content, mode, |unit| match unit {
+                    Unit::Char(char) => {
+                        let mut buffer = [0; 4];
+                        result.extend_from_slice(char.encode_utf8(&mut buffer).as_bytes());
+                    },
+                    Unit::Byte(byte) => result.push(byte),
+                })?;
+                LiteralValue::Bytes(Cow::Owned(result))
+            } else {
+                LiteralValue::Bytes(Cow::Borrowed(content.as_bytes()))
+            }
+        },
+    };
+
+    // chars and bytes hold exactly one value
+    let units = match (&value, mode) {
+        (LiteralValue::Str(value), Mode::Char) => value.chars().count(),
+        (LiteralValue::Bytes(value), Mode::Byte) => value.len(),
+        _ => 1,
+    };
+    match units {
+        0 => error(EscapeErrorKind::ZeroChars, 0),
+        1 => Ok(value),
+        _ => error(EscapeErrorKind::MoreThanOneChar, 0),
+    }
+}
+
+/// Raw literals have no escapes but the same restrictions on the chars, and
+/// their `\r\n` are read as `\n` too
+pub(crate) fn decode_raw(content: &str, mode: Mode) -> Result<LiteralValue<'_>, EscapeError> {
+    for (offset, char) in content.char_indices() {
+        let kind = match char {
+            '\r' if !content[offset + 1..].starts_with('\n') => EscapeErrorKind::BareCarriageReturn,
+            '\0' if mode == Mode::CStr => EscapeErrorKind::NulInCStr,
+            _ if mode.is_byte() && !char.is_ascii() => EscapeErrorKind::NonAsciiCharInByte,
+            _ => continue,
+        };
+        return error(kind, offset);
+    }
+    let value = match content.contains('\r') {
+        true => Cow::Owned(content.replace("\r\n", "\n")),
+        false => Cow::Borrowed(content),
+    };
+    Ok(match mode {
+        Mode::Char | Mode::Str => LiteralValue::Str(value),
+        Mode::Byte | Mode::ByteStr | Mode::CStr => LiteralValue::Bytes(match value {
+            Cow::Owned(value) => Cow::Owned(value.into_bytes()),
+            Cow::Borrowed(value) => Cow::Borrowed(value.as_bytes()),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod test_escape {
+    use super::*;
+    use crate::Literal;
+    use EscapeErrorKind::*;
+
+    fn value(source: &str) -> Result<LiteralValue<'_>, EscapeError> {
+        Literal::try_from(source).unwrap().value().unwrap()
+    }
+
+    fn str_value(value: &str) -> Result<LiteralValue<'_>, EscapeError> {
+        Ok(LiteralValue::Str(Cow::Borrowed(value)))
+    }
+
+    fn bytes_value(value: &[u8]) -> Result<LiteralValue<'_>, EscapeError> {
+        Ok(LiteralValue::Bytes(Cow::Borrowed(value)))
+    }
+
+    #[test]
+    fn decode_literals() {
+        assert_eq!(value(r#""plain""#), str_value("plain"));
+        assert!(matches!(value(r#""plain""#), Ok(LiteralValue::Str(Cow::Borrowed(_)))));
+        assert_eq!(value(r#""a\n\t\x7F\u{1F600}\u{00_e9}\"""#), str_value("a\n\t\x7F\u{1F600}é\""));
+        assert_eq!(value("\"line \\\n     continued\""), str_value("line continued"));
+        assert_eq!(value(r#"r"\n""#), str_value("\\n"));
+        assert_eq!(value(r"'\''"), str_value("'"));
+        assert_eq!(value(r"'\u{10FFFF}'"), str_value("\u{10FFFF}"));
+        assert_eq!(value(r"b'\xFF'"), bytes_value(&[0xFF]));
+        assert_eq!(value(r#"b"a\x80\0""#), bytes_value(b"a\x80\0"));
+        assert_eq!(value(r#"c"é\xFF\u{e9}""#), bytes_value(b"\xC3\xA9\xFF\xC3\xA9"));
+        assert_eq!(value(r##"br#"\"#"##), bytes_value(b"\\"));
+        assert_eq!(Literal::try_from("1u8").unwrap().value(), None);
+
+        let error = |kind, offset| Err(EscapeError { kind, offset });
+        assert_eq!(value(r#""ab\q""#), error(InvalidEscape('q'), 2));
+        assert_eq!(value(r#""\x80""#), error(OutOfRangeHexEscape, 0));
+        assert_eq!(value(r#""\x7""#), error(TooShortHexEscape, 0));
+        assert_eq!(value(r#""\xZZ""#), error(InvalidCharInHexEscape, 0));
+        assert_eq!(value(r#""\x+1""#), error(InvalidCharInHexEscape, 0));
+        assert_eq!(value(r"'\x+1'"), error(InvalidCharInHexEscape, 0));
+        assert_eq!(value(r#""\xé""#), error(InvalidCharInHexEscape, 0));
+        assert_eq!(value(r#""\u1234""#), error(NoBraceInUnicodeEscape, 0));
+        assert_eq!(value(r#""\u{}""#), error(EmptyUnicodeEscape, 0));
+        assert_eq!(value(r#""\u{1234567}""#), error(OverlongUnicodeEscape, 0));
+        assert_eq!(value(r#""\u{D800}""#), error(LoneSurrogateUnicodeEscape, 0));
+        assert_eq!(value(r#""\u{110000}""#), error(OutOfRangeUnicodeEscape, 0));
+        assert_eq!(value(r#""\u{12g}""#), error(InvalidCharInUnicodeEscape, 0));
+        assert_eq!(value(r"b'\u{41}'"), error(UnicodeEscapeInByte, 0));
+        assert_eq!(value(r#"b"é""#), error(NonAsciiCharInByte, 0));
+        assert_eq!(value(r#"c"a\0""#), error(NulInCStr, 1));
+        assert_eq!(value("\"a\rb\""), error(BareCarriageReturn, 1));
+        assert_eq!(value(r"'\nb'"), error(MoreThanOneChar, 0));
+
+        // `\r\n` is read as `\n`, a lone `\r` is an error
+        assert_eq!(value("\"a\r\nb\""), Ok(LiteralValue::Str(Cow::Owned(String::from("a\nb")))));
+        assert_eq!(value("b\"a\r\nb\""), Ok(LiteralValue::Bytes(Cow::Owned(b"a\nb".to_vec()))));
+        assert_eq!(value("\"line \\\r\n     continued\""), Ok(LiteralValue::Str(Cow::Owned(String::from("line continued")))));
+        assert_eq!(value("r\"a\r\nb\""), Ok(LiteralValue::Str(Cow::Owned(String::from("a\nb")))));
+        assert_eq!(value("\"a\r\""), error(BareCarriageReturn, 1));
+        assert_eq!(value("b\"\r\rb\""), error(BareCarriageReturn, 0));
+        assert_eq!(value("r\"a\rb\""), error(BareCarriageReturn, 1));
+        assert_eq!(value("'\t'"), str_value("\t"));
+    }
+}
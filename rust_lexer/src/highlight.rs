@@ -0,0 +1,220 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use crate::{Keyword, Lexer, Literal, LosslessToken, Symbol, Token};
+
+/// What a piece of source is, to color it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightClass {
+    Keyword,
+    Identifier,
+    /// The name and the `!` of a macro call, as `println!`
+    Macro,
+    Lifetime,
+    String,
+    Char,
+    Number,
+    Bool,
+    Comment,
+    DocComment,
+    /// All of an attribute from the `#` to the closing `]`
+    Attribute,
+    Symbol,
+    Error,
+}
+
+impl HighlightClass {
+    /// The CSS class of the HTML spans
+    pub const fn css_class(&self) -> &'static str {
+        use HighlightClass::*;
+        match self {
+            Keyword => "kw",
+            Identifier => "ident",
+            Macro => "macro",
+            Lifetime => "lifetime",
+            String => "string",
+            Char => "char",
+            Number => "number",
+            Bool => "bool",
+            Comment => "comment",
+            DocComment => "doccomment",
+            Attribute => "attr",
+            Symbol => "symbol",
+            Error => "error",
+        }
+    }
+
+    /// The SGR parameters of the ANSI escape code, empty for no color
+    pub const fn ansi_color(&self) -> &'static str {
+        use HighlightClass::*;
+        match self {
+            Keyword => "35",
+            Identifier | Symbol => "",
+            Macro => "34",
+            Lifetime => "33",
+            String | Char => "32",
+            Number | Bool => "36",
+            Comment => "90",
+            DocComment => "3;90",
+            Attribute => "33",
+            Error => "4;31",
+        }
+    }
+}
+
+/// The style of the HTML classes, included in [`highlight_html`]
+pub const HIGHLIGHT_CSS: &str = "\
+pre.rust { background: #fafafa; padding: 0.5em; }
+pre.rust .kw { color: #8959a8; }
+pre.rust .macro { color: #3e999f; }
+pre.rust .lifetime { color: #b76514; }
+pre.rust .string, pre.rust .char { color: #718c00; }
+pre.rust .number, pre.rust .bool { color: #c82829; }
+pre.rust .comment { color: #8e908c; }
+pre.rust .doccomment { color: #4d4d4c; font-style: italic; }
+pre.rust .attr { color: #c99e00; }
+pre.rust .error { text-decoration: underline wavy red; }
+";
+
+/// Split the source in pieces and classify them, the whitespace between the
+/// tokens has no class. Concatenating the pieces gives back the source.
+pub fn highlight<'a>(lexer: Lexer<'a>) -> Vec<(Option<HighlightClass>, &'a str)> {
+    let tokens = lexer.lossless().collect::<Vec<_>>();
+    let mut pieces = Vec::with_capacity(2 * tokens.len());
+    // how many brackets of the current attribute are open, if in one
+    let mut attribute_depth: Option<usize> = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if !token.leading_trivia.is_empty() {
+            pieces.push((None, token.leading_trivia));
+        }
+        if token.text.is_empty() {
+            continue;
+        }
+
+        let is_attribute_start = matches!(token.token.token, Token::Symbol(Symbol::Pound | Symbol::Shebang))
+            && symbol_at(&tokens, index + 1) == Some(Symbol::OpenBraket);
+        if is_attribute_start && attribute_depth.is_none() {
+            attribute_depth = Some(0);
+        }
+        let class = match attribute_depth {
+            Some(depth) => {
+                // track the brackets to find where the attribute ends
+                attribute_depth = match token.token.token {
+                    Token::Symbol(Symbol::OpenBraket) => Some(depth + 1),
+                    Token::Symbol(Symbol::CloseBraket) if depth == 1 => None,
+                    Token::Symbol(Symbol::CloseBraket) => Some(depth.saturating_sub(1)),
+                    _ => Some(depth),
+                };
+                HighlightClass::Attribute
+            },
+            None => classify(&tokens, index),
+        };
+        pieces.push((Some(class), token.text));
+    }
+    pieces
+}
+
+fn symbol_at(tokens: &[LosslessToken<'_>], index: usize) -> Option<Symbol> {
+    match tokens.get(index)?.token.token {
+        Token::Symbol(symbol) => Some(symbol),
+        _ => None,
+    }
+}
+
+fn classify(tokens: &[LosslessToken<'_>], index: usize) -> HighlightClass {
+    let is_identifier = |index: usize| matches!(
+        tokens.get(index).map(|token| &token.token.token),
+        Some(Token::Identifier(_))
+    );
+    match &tokens[index].token.token {
+        Token::Comment(comment) if comment.is_doc() => HighlightClass::DocComment,
+        Token::Comment(_) => HighlightClass::Comment,
+        Token::Literal(literal) => match literal {
+            Literal::Bool(_) => HighlightClass::Bool,
+            Literal::Integer(_) | Literal::Float(_) => HighlightClass::Number,
+            Literal::Char(_) | Literal::Byte(_) => HighlightClass::Char,
+            _ => HighlightClass::String,
+        },
+        Token::Lifetime(_) => HighlightClass::Lifetime,
+        Token::Keyword(Keyword::True | Keyword::False) => HighlightClass::Bool,
+        Token::Keyword(_) => HighlightClass::Keyword,
+        Token::Identifier(_) if symbol_at(tokens, index + 1) == Some(Symbol::Not) => HighlightClass::Macro,
+        Token::Identifier(_) => HighlightClass::Identifier,
+        Token::Symbol(Symbol::Not) if index > 0 && is_identifier(index - 1) => HighlightClass::Macro,
+        Token::Symbol(_) | Token::Empty => HighlightClass::Symbol,
+        Token::Error { .. } => HighlightClass::Error,
+    }
+}
+
+/// Render the source as a `<pre class="rust">` block with a `<span>` for
+/// each classified piece, preceded by the `<style>` of the classes
+pub fn highlight_html(lexer: Lexer<'_>) -> String {
+    let mut html = String::new();
+    let _ = write!(html, "<style>\n{}</style>\n<pre class=\"rust\"><code>", HIGHLIGHT_CSS);
+    for (class, text) in highlight(lexer) {
+        match class {
+            Some(class) => {
+                let _ = write!(html, "<span class=\"{}\">", class.css_class());
+                escape_html(&mut html, text);
+                html.push_str("</span>");
+            },
+            None => escape_html(&mut html, text),
+        }
+    }
+    html.push_str("</code></pre>\n");
+    html
+}
+
+fn escape_html(html: &mut String, text: &str) {
+    for char in text.chars() {
+        match char {
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '&' => html.push_str("&amp;"),
+            '"' => html.push_str("&quot;"),
+            _ => html.push(char),
+        }
+    }
+}
+
+/// Render the source with ANSI escape codes to print it on a terminal
+pub fn highlight_ansi(lexer: Lexer<'_>) -> String {
+    let mut output = String::new();
+    for (class, text) in highlight(lexer) {
+        match class.map(|class| class.ansi_color()) {
+            Some(color) if !color.is_empty() => {
+                let _ = write!(output, "\x1b[{}m{}\x1b[0m", color, text);
+            },
+            _ => output.push_str(text),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod test_highlight {
+    use super::*;
+    #[test]
+    fn classify_pieces() {
+        let source = "#[cfg(a)]\n/// doc\nfn f<'a>() { println!(\"<{}>\", 1 != 2, true); }";
+        let pieces = highlight(Lexer::new(source));
+        assert_eq!(pieces.iter().map(|(_, text)| *text).collect::<String>(), source);
+        let classes = pieces.iter()
+            .filter_map(|(class, text)| Some((class.as_ref()?.css_class(), *text)))
+            .collect::<Vec<_>>();
+        assert_eq!(classes, [
+            ("attr", "#"), ("attr", "["), ("attr", "cfg"), ("attr", "("), ("attr", "a"), ("attr", ")"), ("attr", "]"),
+            ("doccomment", "/// doc"),
+            ("kw", "fn"), ("ident", "f"), ("symbol", "<"), ("lifetime", "'a"), ("symbol", ">"),
+            ("symbol", "()"), ("symbol", "{"),
+            ("macro", "println"), ("macro", "!"), ("symbol", "("), ("string", "\"<{}>\""), ("symbol", ","),
+            ("number", "1"), ("symbol", "!="), ("number", "2"), ("symbol", ","), ("bool", "true"),
+            ("symbol", ")"), ("symbol", ";"), ("symbol", "}"),
+        ]);
+
+        let html = highlight_html(Lexer::new("a < \"&\""));
+        assert!(html.ends_with("<pre class=\"rust\"><code><span class=\"ident\">a</span> <span class=\"symbol\">&lt;</span> <span class=\"string\">&quot;&amp;&quot;</span></code></pre>\n"));
+        assert_eq!(highlight_ansi(Lexer::new("fn a")), "\x1b[35mfn\x1b[0m a");
+    }
+}
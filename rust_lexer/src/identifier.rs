@@ -14,6 +14,22 @@ impl<'a> Identifier<'a> {
     pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    /// If the identifier was written as `r#ident`
+    pub fn is_raw(&self) -> bool {
+        self.0.starts_with("r#")
+    }
+
+    /// The identifier without the `r#` prefix of raw identifiers
+    pub fn name(&self) -> &'a str {
+        self.0.strip_prefix("r#").unwrap_or(self.0)
+    }
+}
+
+impl<'a> core::fmt::Display for Identifier<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.0)
+    }
 }
 
 impl<'a> From<Identifier<'a>> for String {
@@ -25,48 +41,23 @@ impl<'a> From<Identifier<'a>> for String {
 impl<'a> TryFrom<&'a str> for Identifier<'a> {
     type Error = ();
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let mut chars = value.chars();
+        // raw identifiers are `r#` followed by a normal identifier, this
+        // also rejects raw strings such as `r#"..."#`
+        let prefix_len = if value.starts_with("r#") { 2 } else { 0 };
+
+        let mut chars = value[prefix_len..].chars();
         let first_char = chars.next().ok_or(())?;
         // check if the first char is valid
         if !is_xid_start(first_char) && first_char != '_' {
             return Err(());
         }
 
-        let mut index_length = first_char.len_utf8();
-
-        // check if it's a raw identifier
-        if let Some(second_char) = chars.next() {
-            if !is_whitespace(second_char) && (is_xid_continue(second_char) || second_char == '#') {
-                index_length += second_char.len_utf8();
-            } else {
-                return Ok(Identifier(&value[..index_length]));   
-            }
-        } else {
-            return Ok(Identifier(&value[..index_length]));   
-        }
-
-        // check if it's a raw string
-        if let Some(third_char) = chars.next() {
-            // It's a raw string
-            if third_char == '"' {
-                return Err(());   
-            }
-            if is_whitespace(third_char) || !is_xid_continue(third_char) {
-                return Ok(Identifier(&value[..index_length])); 
-            } else {
-                index_length += third_char.len_utf8();
-            }
-        } else {
-            return Ok(Identifier(&value[..index_length]));   
-        }
-
-
         // find the first non-identifier char so we know where to stop
-        while let Some(current_char) = chars.next() {
-            if is_whitespace(current_char) || !is_xid_continue(current_char) {
+        let mut index_length = prefix_len + first_char.len_utf8();
+        for current_char in chars {
+            if !is_xid_continue(current_char) {
                 break
             }
-
             index_length += current_char.len_utf8();
         }
 
@@ -88,6 +79,13 @@ mod test_identifiers {
         assert_eq!(Identifier("foo"), Identifier::try_from("foo (").unwrap());
         assert_eq!(Identifier("_identifier"), Identifier::try_from("_identifier (").unwrap());
         assert_eq!(Identifier("r#true"), Identifier::try_from("r#true (").unwrap());
+        assert_eq!(Identifier("r#type"), Identifier::try_from("r#type").unwrap());
+        assert_eq!(Err(()), Identifier::try_from("r# a"));
+        assert_eq!(Err(()), Identifier::try_from("r##\"aaa\"#"));
+        assert_eq!(Identifier("r"), Identifier::try_from("r #").unwrap());
+        assert_eq!(Identifier("br"), Identifier::try_from("br\n").unwrap());
+        assert_eq!("type", Identifier::try_from("r#type").unwrap().name());
+        assert!(Identifier::try_from("r#type").unwrap().is_raw());
         assert_eq!(Identifier("Москва"), Identifier::try_from("Москва(").unwrap());
         assert_eq!(Identifier("𫞎𫞔"), Identifier::try_from("𫞎𫞔 (").unwrap());
         //assert_eq!(Identifier("東京"), Identifier::try_from("東京(").unwrap());
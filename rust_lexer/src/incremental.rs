@@ -0,0 +1,203 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+use crate::{offset_in, Identifier, Lexer, LexerErrorKind, Position, Span, SpannedToken, Symbol, Token};
+
+/// A change to a source text, the bytes in `range` are replaced by `text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit<'e> {
+    pub range: Range<usize>,
+    pub text: &'e str,
+}
+
+impl<'e> Edit<'e> {
+    /// Return the source with the edit applied
+    pub fn apply(&self, source: &str) -> String {
+        let mut result = String::with_capacity(source.len() + self.text.len() - self.range.len());
+        result.push_str(&source[..self.range.start]);
+        result.push_str(self.text);
+        result.push_str(&source[self.range.end..]);
+        result
+    }
+}
+
+/// Lexing a token can look at up to two chars after its end, e.g. `1.f`
+/// is an integer but `1.5` is a float, so this many tokens before the edit
+/// are lexed again. The prefix of a raw string and its `#` are lexed again
+/// too, see [`raw_prefix_start`].
+const LOOKBEHIND_TOKENS: usize = 2;
+
+/// Go back over the tokens before `index` that an edit could turn into the
+/// start of a raw string: `br####` is an identifier and four `#` until a
+/// `"` follows it, and the `r` of `r####` an error as it's not a raw
+/// identifier.
+fn raw_prefix_start(tokens: &[SpannedToken<'_>], mut index: usize) -> usize {
+    while index > 0 && index < tokens.len() {
+        let (previous, token) = (&tokens[index - 1], &tokens[index]);
+        let is_adjacent = previous.span.end.byte_offset == token.span.start.byte_offset;
+        let is_raw_prefix = matches!(previous.token, Token::Symbol(Symbol::Pound))
+            || matches!(previous.token, Token::Identifier(Identifier("r" | "br" | "cr")))
+            || matches!(previous.token, Token::Error { kind: LexerErrorKind::StrayChar('r'), .. });
+        if !is_adjacent || !is_raw_prefix {
+            break;
+        }
+        index -= 1;
+    }
+    index
+}
+
+/// Re-point the slices of a token from the old source to the new one
+struct Rebase<'o, 'b> {
+    old_source: &'o str,
+    new_source: &'b str,
+    delta: isize,
+}
+
+impl<'o, 'b> Rebase<'o, 'b> {
+    fn str(&self, value: &str) -> &'b str {
+        let offset = offset_in(self.old_source, value).expect("the old tokens must be lexed from the old source");
+        let start = (offset as isize + self.delta) as usize;
+        &self.new_source[start..start + value.len()]
+    }
+
+    fn token(&self, token: &Token<'_>, span: Span) -> Token<'b> {
+        match token.map_strs(|value| self.str(value)) {
+            Token::Error { kind, .. } => Token::Error { span, kind },
+            token => token,
+        }
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Lex again the source of this lexer, which must be `old_source` with
+    /// `edit` applied, reusing the tokens lexed from `old_source` that are
+    /// not affected by the edit and shifting their spans.
+    ///
+    /// Only the edited region is lexed, up to the first token that starts
+    /// where an old one did. The diagnostics of the reused tokens are not
+    /// reported again, and the lexer is exhausted afterwards.
+    ///
+    /// Panics if the old tokens don't borrow `old_source`, as the ones of a
+    /// lexer over it do.
+    pub fn relex(&mut self, old_source: &str, old_tokens: &[SpannedToken<'_>], edit: &Edit<'_>) -> Vec<SpannedToken<'a>> {
+        let new_source = self.original_data;
+        let delta = edit.text.len() as isize - edit.range.len() as isize;
+
+        // the old tokens before the edit are kept as they are
+        let first_dirty = old_tokens.iter()
+            .position(|token| token.span.end.byte_offset >= edit.range.start)
+            .unwrap_or(old_tokens.len())
+            .saturating_sub(LOOKBEHIND_TOKENS);
+        let first_dirty = raw_prefix_start(old_tokens, first_dirty);
+        let start = match old_tokens.get(first_dirty) {
+            Some(token) if token.span.start.byte_offset <= edit.range.start => token.span.start,
+            // the edit is before the first token
+            _ => Position::default(),
+        };
+
+        let keep = Rebase { old_source, new_source, delta: 0 };
+        let mut result = old_tokens[..first_dirty].iter()
+            .map(|token| SpannedToken {
+                token: keep.token(&token.token, token.span),
+                span: token.span,
+            })
+            .collect::<Vec<_>>();
+
+        // where the end of the edit moved, to shift the old spans
+        let mut old_end = start;
+        old_end.advance(&old_source[start.byte_offset..edit.range.end]);
+        let mut new_end = start;
+        new_end.advance(&new_source[start.byte_offset..edit.range.start + edit.text.len()]);
+        let shift = |position: Position| Position {
+            byte_offset: (position.byte_offset as isize + delta) as usize,
+            line: position.line - old_end.line + new_end.line,
+            column: if position.line == old_end.line {
+                position.column - old_end.column + new_end.column
+            } else {
+                position.column
+            },
+        };
+
+        self.position = start;
+        self.remaining_text = &new_source[start.byte_offset..];
+
+        let mut old_index = first_dirty;
+        let mut is_synced = false;
+        while let Some(token) = self.next_spanned() {
+            let new_start = token.span.start.byte_offset as isize;
+            // skip the old tokens we went past
+            while old_index < old_tokens.len()
+                && (old_tokens[old_index].span.start.byte_offset as isize + delta) < new_start {
+                old_index += 1;
+            }
+            is_synced = old_tokens.get(old_index).map(|old| {
+                old.span.start.byte_offset >= edit.range.end
+                && old.span.start.byte_offset as isize + delta == new_start
+            }).unwrap_or(false);
+            if is_synced {
+                break;
+            }
+            result.push(token);
+        }
+
+        if is_synced {
+            // the rest of the text is unchanged, so are its tokens
+            let rebase = Rebase { old_source, new_source, delta };
+            result.extend(old_tokens[old_index..].iter().map(|old| {
+                let span = Span {
+                    file_id: self.file_id,
+                    start: shift(old.span.start),
+                    end: shift(old.span.end),
+                };
+                SpannedToken {
+                    token: rebase.token(&old.token, span),
+                    span,
+                }
+            }));
+            self.remaining_text = "";
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test_incremental {
+    use super::*;
+
+    fn check(old_source: &str, range: Range<usize>, text: &str) {
+        let edit = Edit { range, text };
+        let new_source = edit.apply(old_source);
+        let old_tokens = Lexer::new(old_source).with_recovery().spanned().collect::<Vec<_>>();
+        let relexed = Lexer::new(&new_source).with_recovery().relex(old_source, &old_tokens, &edit);
+        let expected = Lexer::new(&new_source).with_recovery().spanned().collect::<Vec<_>>();
+        assert_eq!(relexed, expected, "editing {:?} into {:?}", old_source, new_source);
+    }
+
+    #[test]
+    fn relex_edits() {
+        let source = "fn main() {\n    let x = 1.f;\n    /* a */ let y = \"s\";\n}\n";
+        // extend an identifier
+        check(source, 4..4, "_2");
+        // turn `1.f` in a float
+        check(source, 26..27, "5");
+        // insert lines
+        check(source, 12..12, "\n\n let z = 'a';");
+        // delete everything in the middle
+        check(source, 5..40, "");
+        // open a comment that swallows everything
+        check(source, 16..16, "/* ");
+        // open a string
+        check(source, 16..16, "\"");
+        // change a multi byte char
+        check("let é = 'é'; a", 4..6, "ü");
+        // edit at the very start and end
+        check(source, 0..0, "pub ");
+        check(source, source.len()..source.len(), "// end");
+        check("", 0..0, "a b");
+        // close a raw string whose prefix and hashes are further back
+        check("br#### x", 6..6, "\"a\"####");
+        check("r#### x", 5..5, "\"a\"####");
+        check("let s = r## x", 12..12, "\"a\"##");
+    }
+}
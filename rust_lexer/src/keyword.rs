@@ -1,4 +1,4 @@
-use crate::Identifier;
+use crate::{Edition, Identifier};
 
 macro_rules! impl_keyword {
     ($($variant:ident => $value:literal,)*) => {
@@ -28,6 +28,12 @@ impl From<Keyword> for &'static str {
     }
 }
 
+impl core::fmt::Display for Keyword {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str((*self).into())
+    }
+}
+
 impl<'a> TryFrom<Identifier<'a>> for Keyword {
     type Error = ();
     fn try_from(value: Identifier<'a>) -> Result<Self, Self::Error> {
@@ -51,6 +57,15 @@ mod test_keyword {
                 Keyword::try_from(Identifier::try_from($value).unwrap()).unwrap(),
             );
         )*
+
+        let classify = |word, edition| Keyword::classify(Identifier::try_from(word).unwrap(), edition);
+        assert_eq!(classify("async", Edition::Edition2015), None);
+        assert_eq!(classify("try", Edition::Edition2015), None);
+        assert_eq!(classify("try", Edition::Edition2018), Some(Keyword::Try));
+        assert_eq!(classify("gen", Edition::Edition2021), None);
+        assert_eq!(classify("gen", Edition::Edition2024), Some(Keyword::Gen));
+        assert_eq!(classify("box", Edition::Edition2015), Some(Keyword::Box));
+        assert_eq!(classify("union", Edition::Edition2024), None);
     }
 }
     };
@@ -110,6 +125,7 @@ impl_keyword!(
     Box => "box",
     Dyn => "dyn",
     For => "for",
+    Gen => "gen",
     Let => "let",
     Mod => "mod",
     Mut => "mut",
@@ -122,4 +138,21 @@ impl_keyword!(
     Fn => "fn",
     If => "if",
     In => "in",
-);
\ No newline at end of file
+);
+impl Keyword {
+    /// The keyword that the identifier is in the given edition, `None` if
+    /// it is a plain identifier there. The weak keyword `union` is always an
+    /// identifier as it's only a keyword before the name of a union, which
+    /// the parser tells.
+    pub fn classify(identifier: Identifier<'_>, edition: Edition) -> Option<Keyword> {
+        use Keyword::*;
+        let keyword = Keyword::try_from(identifier).ok()?;
+        let since = match keyword {
+            Union => return None,
+            Async | Await | Dyn | Try => Edition::Edition2018,
+            Gen => Edition::Edition2024,
+            _ => Edition::Edition2015,
+        };
+        (edition >= since).then_some(keyword)
+    }
+}
@@ -3,10 +3,15 @@
 use core::convert::TryFrom;
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+use alloc::vec::Vec;
 
 mod chars_constants;
 mod peekable;
 pub use peekable::*;
+mod config;
+pub use config::*;
 mod keyword;
 pub use keyword::*;
 mod symbol;
@@ -17,28 +22,64 @@ mod identifier;
 pub use identifier::*;
 mod comment;
 pub use comment::*;
+mod lifetime;
+pub use lifetime::*;
+mod span;
+pub use span::*;
+mod lossless;
+pub use lossless::*;
+mod highlight;
+pub use highlight::*;
+mod escape;
+pub use escape::{EscapeError, EscapeErrorKind, LiteralValue};
+mod doc;
+pub use doc::*;
+mod token_tree;
+pub use token_tree::*;
+mod incremental;
+pub use incremental::*;
+mod streaming;
+pub use streaming::*;
+#[cfg(feature = "proc_macro2")]
+mod proc_macro2_interop;
+#[cfg(feature = "proc_macro2")]
+pub use proc_macro2_interop::*;
 
-#[derive(Debug)]
+/// Why a piece of source could not be tokenized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexerErrorKind {
+    UnterminatedString,
+    UnterminatedChar,
+    UnterminatedBlockComment,
+    BadEscape(EscapeErrorKind),
+    StrayChar(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LexerError<'a> {
     UnexpectedEndOfFile(Span),
     CannotTokenize{
-        source: &'a str,
-        span:Span
+        kind: LexerErrorKind,
+        /// The first line of the offending text
+        snippet: &'a str,
+        span: Span,
     },
 }
 
 type Result<'a, T> = core::result::Result<T, LexerError<'a>>;
 
-#[derive(Debug, Clone, Default)]
-pub struct Span {
-    line: usize,
-    byte_offset: usize,
-}
-
+/// Split a source in tokens. Without [`Lexer::with_recovery`] the iteration
+/// ends at the first error, which is then in [`Lexer::diagnostics`]: a
+/// stream that ends early is only told apart from a complete one by checking
+/// the diagnostics once it's over.
 pub struct Lexer<'a> {
     original_data: &'a str,
     remaining_text: &'a str,
-    span: Span,
+    file_id: usize,
+    position: Position,
+    recovery: bool,
+    config: LexerConfig,
+    diagnostics: Vec<LexerError<'a>>,
 }
 
 impl<'a> Lexer<'a> {
@@ -46,76 +87,251 @@ impl<'a> Lexer<'a> {
         Self {
             original_data: data,
             remaining_text: data,
-            span: Span::default(),
+            file_id: 0,
+            position: Position::default(),
+            recovery: false,
+            config: LexerConfig::default(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Set the file id reported in the spans of the tokens
+    pub fn with_file_id(mut self, file_id: usize) -> Self {
+        self.file_id = file_id;
+        self
+    }
+
+    /// Instead of stopping at the first error, emit a [`Token::Error`],
+    /// skip the offending text and continue. The errors are collected in
+    /// [`Lexer::diagnostics`].
+    pub fn with_recovery(mut self) -> Self {
+        self.recovery = true;
+        self
+    }
+
+    /// Lex following the given settings, e.g. the keywords of an edition
+    pub fn with_config(mut self, config: LexerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The whole text being lexed
+    pub fn source(&self) -> &'a str {
+        self.original_data
+    }
+
+    /// The errors found so far
+    pub fn diagnostics(&self) -> &[LexerError<'a>] {
+        &self.diagnostics
+    }
+
+    /// The position of the next char to lex
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    #[inline]
+    fn advance(&mut self, len: usize) {
+        let (consumed, rem) = self.remaining_text.split_at(len);
+        self.position.advance(consumed);
+        self.remaining_text = rem;
+    }
+
+    #[inline]
+    fn empty_span(&self) -> Span {
+        Span {
+            file_id: self.file_id,
+            start: self.position,
+            end: self.position,
         }
     }
 
     #[inline]
     pub fn skip_white_space(&mut self) {
-        while let Some(current_char) = self.remaining_text.chars().next() {
-            if !chars_constants::is_whitespace(current_char) {
-                break
-            }
-            if current_char == '\n' {
-                self.span.line += 1;
-            }
-            self.remaining_text = &self.remaining_text[1..];
-            self.span.byte_offset += current_char.len_utf8();
-            
+        let len = self.remaining_text
+            .find(|c| !chars_constants::is_whitespace(c))
+            .unwrap_or(self.remaining_text.len());
+        self.advance(len);
+    }
+
+    /// Build the error for the `len` bytes at the start of the remaining text
+    fn error(&self, kind: LexerErrorKind, len: usize) -> LexerError<'a> {
+        let text = &self.remaining_text[..len];
+        let mut end = self.position;
+        end.advance(text);
+        LexerError::CannotTokenize {
+            kind,
+            snippet: text.lines().next().unwrap_or(""),
+            span: Span {
+                file_id: self.file_id,
+                start: self.position,
+                end,
+            },
         }
     }
 
-    pub fn get_next_token(&mut self) -> Result<Token<'a>> {
-        self.skip_white_space();
+    /// Figure out why the remaining text cannot be tokenized and how much
+    /// of it to skip to resync
+    fn diagnose(&self) -> LexerError<'a> {
+        let text = self.remaining_text;
+        let rest = text.len();
+
+        if text.starts_with("/*") {
+            return self.error(LexerErrorKind::UnterminatedBlockComment, rest);
+        }
+
+        let unprefixed = text.trim_start_matches(['b', 'c', 'r']);
+        let prefix_len = text.len() - unprefixed.len();
+        let is_string_start = prefix_len <= 2 && (
+            unprefixed.starts_with('"') 
+            || (text[..prefix_len].ends_with('r') && unprefixed.trim_start_matches('#').starts_with('"'))
+        );
+        if is_string_start {
+            return self.error(LexerErrorKind::UnterminatedString, rest);
+        }
+
+        if text.starts_with('\'') || text.starts_with("b'") {
+            // the literal goes up to the next quote of the line, as in
+            // `'ab'`, or to the end of the line
+            let open = text.find('\'').unwrap() + 1;
+            let line_end = text[open..].find('\n').map_or(rest, |end| open + end);
+            let len = text[open..line_end].find('\'').map_or(line_end, |close| open + close + 1);
+            return self.error(LexerErrorKind::UnterminatedChar, len);
+        }
+
+        // Defensive unwrap, lex_token already checked that it's not empty
+        let char = text.chars().next().unwrap();
+        self.error(LexerErrorKind::StrayChar(char), char.len_utf8())
+    }
+
+    /// Lex the token at the start of the remaining text without consuming it
+    fn lex_token(&self) -> Result<'a, Token<'a>> {
+        if self.remaining_text.is_empty() {
+            return Err(LexerError::UnexpectedEndOfFile(self.empty_span()));
+        }
 
         macro_rules! try_parse {
             ($type:ident) => {
                 if let Ok(value) = $type::try_from(self.remaining_text) {
-                    self.span.byte_offset += value.len();
-                    let (_extra, rem) = self.remaining_text.split_at(value.len());
-                    self.remaining_text = rem;
                     return Ok(Token::$type(value)) 
                 }
             };
         }
 
         try_parse!(Comment);
-        try_parse!(Literal);
-        try_parse!(Symbol);
+        if let Ok(literal) = Literal::try_from(self.remaining_text) {
+            if let Some(Err(error)) = literal.value() {
+                return Err(self.error(LexerErrorKind::BadEscape(error.kind), literal.len()));
+            }
+            return Ok(Token::Literal(literal));
+        }
+        // don't let an unterminated comment or string be lexed as symbols
+        // and identifiers
+        if let error @ LexerError::CannotTokenize {
+            kind: LexerErrorKind::UnterminatedBlockComment | LexerErrorKind::UnterminatedString, ..
+        } = self.diagnose() {
+            return Err(error);
+        }
+        try_parse!(Lifetime);
 
+        // identifiers go before symbols so that `_foo` is not split in `_`
+        // and `foo`, a lone `_` is the `Underscore` symbol instead
         if let Ok(identifier) = Identifier::try_from(self.remaining_text) {
-            self.span.byte_offset += identifier.len();
-            let (_extra, rem) = self.remaining_text.split_at(identifier.len());
-            self.remaining_text = rem;
-
-            return Ok(if let Ok(keyword) = Keyword::try_from(identifier) {
-                Token::Keyword(keyword)
-            } else {
-                Token::Identifier(identifier)
-            });
+            if identifier.0 != "_" {
+                return Ok(if let Some(keyword) = Keyword::classify(identifier, self.config.edition) {
+                    Token::Keyword(keyword)
+                } else {
+                    Token::Identifier(identifier)
+                });
+            }
         }
 
-        Err(LexerError::CannotTokenize{
-            source: self.original_data,
-            span:self.span.clone(),
+        try_parse!(Symbol);
+
+        Err(self.diagnose())
+    }
+
+    pub fn get_next_spanned_token(&mut self) -> Result<'a, SpannedToken<'a>> {
+        self.skip_white_space();
+        let start = self.position;
+        let token = match self.lex_token() {
+            Ok(token) => token,
+            Err(LexerError::CannotTokenize { kind, snippet, span }) if self.recovery => {
+                self.diagnostics.push(LexerError::CannotTokenize { kind, snippet, span });
+                Token::Error { span, kind }
+            }
+            Err(error) => return Err(error),
+        };
+        self.advance(token.len());
+        Ok(SpannedToken {
+            token,
+            span: Span {
+                file_id: self.file_id,
+                start,
+                end: self.position,
+            },
         })
     }
 
-    pub fn get_token_stream(self) 
-        -> Peekable<Token<'a>, Self, 4096> {
+    pub fn get_next_token(&mut self) -> Result<'a, Token<'a>> {
+        self.get_next_spanned_token().map(|spanned| spanned.token)
+    }
+
+    pub fn get_token_stream(self) -> Peekable<Token<'a>, Self> {
         Peekable::new(self)
     }
+
+    /// Iterate over the tokens together with their spans, the errors are in
+    /// [`SpannedTokens::diagnostics`]
+    pub fn spanned(self) -> SpannedTokens<'a> {
+        SpannedTokens(self)
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Shared logic of the iterators, without recovery the iteration stops
+    /// at the first error which is stored in the diagnostics
+    fn next_spanned(&mut self) -> Option<SpannedToken<'a>> {
+        self.skip_white_space();
+        if self.remaining_text.is_empty() {
+            return None;
+        }
+
+        match self.get_next_spanned_token() {
+            Ok(token) => Some(token),
+            Err(error) => {
+                self.diagnostics.push(error);
+                self.remaining_text = "";
+                None
+            }
+        }
+    }
 }
 
 impl<'a> Iterator for Lexer<'a> {
     type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.remaining_text.is_empty() {
-            return None;
-        }
+        self.next_spanned().map(|spanned| spanned.token)
+    }
+}
+
+/// Iterator over the tokens of a [`Lexer`] and their spans, which ends at
+/// the first error as the lexer does
+pub struct SpannedTokens<'a>(Lexer<'a>);
 
-        Some(self.get_next_token().unwrap())
+impl<'a> SpannedTokens<'a> {
+    /// The errors found so far
+    pub fn diagnostics(&self) -> &[LexerError<'a>] {
+        self.0.diagnostics()
+    }
+}
+
+impl<'a> Iterator for SpannedTokens<'a> {
+    type Item = SpannedToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_spanned()
     }
 }
 
@@ -123,14 +339,160 @@ impl<'a> Iterator for Lexer<'a> {
 pub enum Token<'a> {
     Comment(Comment<'a>),
     Literal(Literal<'a>),
+    Lifetime(Lifetime<'a>),
     Symbol(Symbol),
     Keyword(Keyword),
     Identifier(Identifier<'a>),
+    /// Text that could not be tokenized, only emitted in recovery mode
+    Error {
+        span: Span,
+        kind: LexerErrorKind,
+    },
     Empty,
 }
 
+impl<'a> Token<'a> {
+    /// How many bytes of the source the token spans
+    pub fn len(&self) -> usize {
+        match self {
+            Token::Comment(comment) => comment.len(),
+            Token::Literal(literal) => literal.len(),
+            Token::Lifetime(lifetime) => lifetime.len(),
+            Token::Symbol(symbol) => symbol.len(),
+            Token::Keyword(keyword) => keyword.len(),
+            Token::Identifier(identifier) => identifier.len(),
+            Token::Error { span, .. } => span.len(),
+            Token::Empty => 0,
+        }
+    }
+
+    /// Only [`Token::Empty`] spans no bytes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Comments that are not docs carry no meaning, as whitespace
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Token::Comment(comment) if !comment.is_doc())
+    }
+
+    /// The same token with each of its slices of the source replaced by what
+    /// `map` returns for it, the slices are visited in the order they are in
+    /// the token
+    pub(crate) fn map_strs<'b>(&self, mut map: impl FnMut(&'a str) -> &'b str) -> Token<'b> {
+        use Literal::*;
+        let mut number = |number: &Number<'a>| Number {
+            raw: map(number.raw),
+            base: number.base,
+            digits: map(number.digits),
+            suffix: number.suffix.map(&mut map),
+        };
+        match self {
+            Token::Comment(comment) => Token::Comment(match comment {
+                Comment::LineComment(x) => Comment::LineComment(map(x)),
+                Comment::BlockComment(x) => Comment::BlockComment(map(x)),
+                Comment::InnerLineDoc(x) => Comment::InnerLineDoc(map(x)),
+                Comment::InnerBlockDoc(x) => Comment::InnerBlockDoc(map(x)),
+                Comment::OuterLineDoc(x) => Comment::OuterLineDoc(map(x)),
+                Comment::OuterBlockDoc(x) => Comment::OuterBlockDoc(map(x)),
+            }),
+            Token::Literal(literal) => Token::Literal(match literal {
+                Integer(value) => Integer(number(value)),
+                Float(value) => Float(number(value)),
+                Bool(raw) => Bool(map(raw)),
+                Char(raw) => Char(map(raw)),
+                Byte(raw) => Byte(map(raw)),
+                String(raw) => String(map(raw)),
+                RawString(raw, hashes) => RawString(map(raw), *hashes),
+                Bytes(raw) => Bytes(map(raw)),
+                RawBytes(raw, hashes) => RawBytes(map(raw), *hashes),
+                CString(raw) => CString(map(raw)),
+                RawCString(raw, hashes) => RawCString(map(raw), *hashes),
+            }),
+            Token::Lifetime(lifetime) => Token::Lifetime(Lifetime(map(lifetime.0))),
+            Token::Identifier(identifier) => Token::Identifier(Identifier(map(identifier.0))),
+            Token::Symbol(symbol) => Token::Symbol(*symbol),
+            Token::Keyword(keyword) => Token::Keyword(*keyword),
+            Token::Error { span, kind } => Token::Error { span: *span, kind: *kind },
+            Token::Empty => Token::Empty,
+        }
+    }
+}
+
+/// Where `inner` starts in `outer`, `None` if it's not a slice of it
+pub(crate) fn offset_in(outer: &str, inner: &str) -> Option<usize> {
+    let outer_range = outer.as_bytes().as_ptr_range();
+    let inner_range = inner.as_bytes().as_ptr_range();
+    (outer_range.start <= inner_range.start && inner_range.end <= outer_range.end)
+        .then(|| inner_range.start as usize - outer_range.start as usize)
+}
+
+/// Render the token as it was in the source, errors render as nothing
+impl<'a> core::fmt::Display for Token<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Token::Comment(comment) => comment.fmt(f),
+            Token::Literal(literal) => literal.fmt(f),
+            Token::Lifetime(lifetime) => lifetime.fmt(f),
+            Token::Symbol(symbol) => symbol.fmt(f),
+            Token::Keyword(keyword) => keyword.fmt(f),
+            Token::Identifier(identifier) => identifier.fmt(f),
+            Token::Error { .. } | Token::Empty => Ok(()),
+        }
+    }
+}
+
 impl<'a> Default for Token<'a> {
     fn default() -> Self {
         Token::Empty
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test_lexer {
+    use super::*;
+    #[test]
+    fn recover_from_errors() {
+        let source = "let a = 1 § 2;\nlet b = '\\q';\nlet d = 'ab' + 1;\nlet c = b\"open";
+        let mut lexer = Lexer::new(source).with_recovery();
+        let errors = lexer.by_ref()
+            .filter_map(|token| match token {
+                Token::Error { span, kind } => Some((kind, &source[span.byte_range()])),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(errors, [
+            (LexerErrorKind::StrayChar('§'), "§"),
+            (LexerErrorKind::BadEscape(EscapeErrorKind::InvalidEscape('q')), "'\\q'"),
+            (LexerErrorKind::UnterminatedChar, "'ab'"),
+            (LexerErrorKind::UnterminatedString, "b\"open"),
+        ]);
+        assert_eq!(lexer.diagnostics().len(), 4);
+
+        let mut lexer = Lexer::new("a /* b\nc");
+        assert_eq!(lexer.by_ref().count(), 1);
+        assert_eq!(lexer.diagnostics(), [LexerError::CannotTokenize {
+            kind: LexerErrorKind::UnterminatedBlockComment,
+            snippet: "/* b",
+            span: Span {
+                file_id: 0,
+                start: Position { byte_offset: 2, line: 0, column: 2 },
+                end: Position { byte_offset: 8, line: 1, column: 1 },
+            },
+        }]);
+    }
+
+    #[test]
+    fn keywords_of_editions() {
+        let source = "async fn try_it() { r#try; try {} dyn gen; let union = 1; }";
+        let keywords = |edition| Lexer::new(source)
+            .with_config(LexerConfig { edition })
+            .filter_map(|token| match token {
+                Token::Keyword(keyword) => Some(keyword),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(keywords(Edition::Edition2015), [Keyword::Fn, Keyword::Let]);
+        assert_eq!(keywords(Edition::Edition2021), [Keyword::Async, Keyword::Fn, Keyword::Try, Keyword::Dyn, Keyword::Let]);
+        assert_eq!(keywords(Edition::Edition2024), [Keyword::Async, Keyword::Fn, Keyword::Try, Keyword::Dyn, Keyword::Gen, Keyword::Let]);
+    }
+}
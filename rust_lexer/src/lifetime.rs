@@ -0,0 +1,84 @@
+use crate::Identifier;
+
+/// A lifetime or a loop label such as `'a`, `'static`, `'_` or `'outer`.
+/// The two are the same token, the parser tells them apart from the context,
+/// e.g. `'outer: loop {}` versus `T: 'a`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Lifetime<'a>(pub(crate) &'a str); // the name without the quote
+
+impl<'a> AsRef<str> for Lifetime<'a> {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl<'a> Lifetime<'a> {
+    /// How many bytes of the source the lifetime spans, with its quote
+    pub(crate) fn len(&self) -> usize {
+        self.0.len() + 1
+    }
+
+    /// The name of the lifetime, without the leading quote
+    pub fn name(&self) -> &'a str {
+        self.0
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.0 == "static"
+    }
+
+    /// If it's the anonymous lifetime `'_`
+    pub fn is_anonymous(&self) -> bool {
+        self.0 == "_"
+    }
+}
+
+impl<'a> core::fmt::Display for Lifetime<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "'{}", self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Lifetime<'a> {
+    type Error = ();
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let name = Identifier::try_from(value.strip_prefix('\'').ok_or(())?)?;
+        // `'a'` is a char literal
+        if value[1 + name.len()..].starts_with('\'') {
+            return Err(());
+        }
+        Ok(Lifetime(name.0))
+    }
+}
+
+#[cfg(test)]
+mod test_lifetime {
+    use super::*;
+    use crate::{Lexer, Literal, Symbol, Token};
+    #[test]
+    fn try_to_parse_lifetime() {
+        assert_eq!(Lifetime("a"), Lifetime::try_from("'a>").unwrap());
+        assert_eq!(Lifetime("static"), Lifetime::try_from("'static str").unwrap());
+        assert_eq!(Lifetime("_"), Lifetime::try_from("'_>").unwrap());
+        assert_eq!(Lifetime("outer"), Lifetime::try_from("'outer: loop").unwrap());
+        assert_eq!(Err(()), Lifetime::try_from("'a'"));
+        assert_eq!(Err(()), Lifetime::try_from("'\\n'"));
+
+        let tokens = Lexer::new("fn f<'a>(x: &'a str, c: char) { 'outer: loop { break 'outer; } 'a'; '\\u{1F600}'; '\\''; }")
+            .filter(|token| matches!(token, Token::Lifetime(_) | Token::Literal(_)))
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(tokens, [
+            Token::Lifetime(Lifetime("a")),
+            Token::Lifetime(Lifetime("a")),
+            Token::Lifetime(Lifetime("outer")),
+            Token::Lifetime(Lifetime("outer")),
+            Token::Literal(Literal::Char("a")),
+            Token::Literal(Literal::Char("\\u{1F600}")),
+            Token::Literal(Literal::Char("\\'")),
+        ]);
+
+        let mut lexer = Lexer::new("'outer: ");
+        assert_eq!(lexer.next(), Some(Token::Lifetime(Lifetime("outer"))));
+        assert_eq!(lexer.next(), Some(Token::Symbol(Symbol::Colon)));
+    }
+}
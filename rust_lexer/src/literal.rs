@@ -1,90 +1,327 @@
-use crate::Identifier;
+use crate::chars_constants::*;
+use crate::escape::{decode, decode_raw, Mode};
+use crate::{EscapeError, LiteralValue};
+
+/// The radix of a numeric literal, as selected by its prefix.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Base {
+    Binary,      // 0b
+    Octal,       // 0o
+    Decimal,
+    Hexadecimal, // 0x
+}
+
+impl Base {
+    pub const fn radix(&self) -> u32 {
+        match self {
+            Base::Binary => 2,
+            Base::Octal => 8,
+            Base::Decimal => 10,
+            Base::Hexadecimal => 16,
+        }
+    }
+}
+
+/// An integer or float literal split in its components.
+/// `digits` excludes the base prefix and the suffix but keeps the
+/// underscores, the dot and the exponent.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Number<'a> {
+    pub raw: &'a str,
+    pub base: Base,
+    pub digits: &'a str,
+    pub suffix: Option<&'a str>,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Literal<'a> {
     Bool(&'a str),
-    Integer(&'a str),
-    Float(&'a str),
+    Integer(Number<'a>),
+    Float(Number<'a>),
     Char(&'a str),
     Byte(&'a str),
     String(&'a str),
-    RawString(&'a str),
+    RawString(&'a str, usize),  // content and number of hashes
     Bytes(&'a str),
-    RawBytes(&'a str),
+    RawBytes(&'a str, usize),
+    CString(&'a str),
+    RawCString(&'a str, usize),
 }
 
 impl<'a> Literal<'a> {
     pub fn len(&self) -> usize {
         use Literal::*;
         match self {
-            Bool(raw)      => raw.len(),
-            Integer(raw)   => raw.len(),
-            Float(raw)     => raw.len(),
-            Char(raw)      => raw.len() + 1 + 1,
-            String(raw)    => raw.len() + 1 + 1,
-            RawString(raw) => raw.len() + 3 + 2,
-            Bytes(raw)     => raw.len() + 2 + 1,
-            Byte(raw)      => raw.len() + 2 + 1,
-            RawBytes(raw)  => raw.len() + 4 + 2,
+            Bool(raw)                => raw.len(),
+            Integer(num)             => num.raw.len(),
+            Float(num)               => num.raw.len(),
+            Char(raw)                => raw.len() + 1 + 1,
+            String(raw)              => raw.len() + 1 + 1,
+            RawString(raw, hashes)   => raw.len() + 2 + 1 + 2 * hashes,
+            Bytes(raw)               => raw.len() + 2 + 1,
+            Byte(raw)                => raw.len() + 2 + 1,
+            RawBytes(raw, hashes)    => raw.len() + 3 + 1 + 2 * hashes,
+            CString(raw)             => raw.len() + 2 + 1,
+            RawCString(raw, hashes)  => raw.len() + 3 + 1 + 2 * hashes,
         }
     }
+
+    /// The decoded content of text literals, `None` for numbers and bools.
+    /// Nothing is allocated unless there are escapes to decode.
+    pub fn value(&self) -> Option<Result<LiteralValue<'a>, EscapeError>> {
+        use Literal::*;
+        Some(match self {
+            Bool(_) | Integer(_) | Float(_) => return None,
+            Char(raw) => decode(raw, Mode::Char),
+            String(raw) => decode(raw, Mode::Str),
+            Byte(raw) => decode(raw, Mode::Byte),
+            Bytes(raw) => decode(raw, Mode::ByteStr),
+            CString(raw) => decode(raw, Mode::CStr),
+            RawString(raw, _) => decode_raw(raw, Mode::Str),
+            RawBytes(raw, _) => decode_raw(raw, Mode::ByteStr),
+            RawCString(raw, _) => decode_raw(raw, Mode::CStr),
+        })
+    }
 }
 
 fn get_literal_end<'a>(data: &'a str, opening_tag: &str, closing_tag: &str) -> Option<&'a str> {
-    if !data.starts_with(opening_tag) {
-        return None;
+    let ptr = data.strip_prefix(opening_tag)?;
+
+    let mut is_escaped = false;
+    for (index, char) in ptr.char_indices() {
+        if !is_escaped && ptr[index..].starts_with(closing_tag) {
+            return Some(&ptr[..index]);
+        }
+        is_escaped = !is_escaped && char == '\\';
+    }
+    // not closing tag
+    None
+}
+
+/// Parse a char or byte literal, these hold exactly one char or escape
+/// sequence so that `'a` in `&'a str` is not mistaken for the start of one.
+fn get_char_literal_end<'a>(data: &'a str, opening_tag: &str) -> Option<&'a str> {
+    let ptr = data.strip_prefix(opening_tag)?;
+    let mut chars = ptr.char_indices();
+
+    let end = match chars.next()? {
+        (_, '\'') | (_, '\n') => return None,
+        (_, '\\') => {
+            // the escape can be long, e.g. `\x7F` or `\u{1F600}`
+            let (index, escaped) = chars.next()?;
+            let start = index + escaped.len_utf8();
+            start + ptr[start..].find(['\'', '\n'])?
+        },
+        (_, char) => char.len_utf8(),
+    };
+
+    if ptr[end..].starts_with('\'') {
+        Some(&ptr[..end])
+    } else {
+        None
     }
-    // skip the
-    let (_, ptr) = data.split_at(opening_tag.len());
+}
+
+/// Parse a raw literal such as `r##"..."##` given its prefix (`r`, `br` or
+/// `cr`), returning the content and the number of hashes.
+fn get_raw_literal_end<'a>(data: &'a str, prefix: &str) -> Option<(&'a str, usize)> {
+    let ptr = data.strip_prefix(prefix)?;
+    let hashes = ptr.len() - ptr.trim_start_matches('#').len();
+    let ptr = ptr[hashes..].strip_prefix('"')?;
 
     let mut index = 0;
-    let mut is_escaped = false;
-    for char in ptr.chars() {
-        if char == '\\' {
-            is_escaped ^= true;
-        } else {
-            is_escaped = false;
+    while let Some(offset) = ptr[index..].find('"') {
+        index += offset;
+        let closing_hashes = ptr[index + 1..].len() - ptr[index + 1..].trim_start_matches('#').len();
+        if closing_hashes >= hashes {
+            return Some((&ptr[..index], hashes));
         }
+        index += 1;
+    }
+    // not closing tag
+    None
+}
+
+/// Length of the prefix of `data` made only of chars that satisfy `is_digit`
+/// or are underscores.
+fn digits_len(data: &str, is_digit: impl Fn(char) -> bool) -> usize {
+    data.char_indices()
+        .find(|(_, c)| !is_digit(*c) && *c != '_')
+        .map(|(i, _)| i)
+        .unwrap_or(data.len())
+}
+
+/// Length of the identifier-like suffix at the start of `data`, if any.
+fn suffix_len(data: &str) -> usize {
+    let mut chars = data.char_indices();
+    match chars.next() {
+        Some((_, c)) if is_xid_start(c) || c == '_' => {},
+        _ => return 0,
+    }
+    chars.find(|(_, c)| !is_xid_continue(*c))
+        .map(|(i, _)| i)
+        .unwrap_or(data.len())
+}
 
-        if !is_escaped {
-            if ptr[index..].starts_with(closing_tag) {
-                return Some(&ptr[..index]);
+/// Length of the exponent (`e`, optional sign, digits) at the start of
+/// `data`, or 0 if there is no valid exponent.
+fn exponent_len(data: &str) -> usize {
+    if !data.starts_with(['e', 'E']) {
+        return 0;
+    }
+    let mut index = 1;
+    if data[index..].starts_with(['+', '-']) {
+        index += 1;
+    }
+    let digits = digits_len(&data[index..], |c| c.is_ascii_digit());
+    // an exponent needs at least one actual digit
+    if !data[index..index + digits].chars().any(|c| c.is_ascii_digit()) {
+        return 0;
+    }
+    index + digits
+}
+
+/// Lex an integer or float literal, following the rules of
+/// https://doc.rust-lang.org/reference/tokens.html#number-literals
+fn parse_number(value: &str) -> Option<Literal<'_>> {
+    if !value.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let base = match value.get(..2) {
+        Some("0b") => Base::Binary,
+        Some("0o") => Base::Octal,
+        Some("0x") => Base::Hexadecimal,
+        _ => Base::Decimal,
+    };
+
+    let mut is_float = false;
+    let (prefix_len, digits_end) = if base == Base::Decimal {
+        let mut index = digits_len(value, |c| c.is_ascii_digit());
+        let rest = &value[index..];
+        // `1.0` and `1.` are floats, `1..2`, `1.foo()` and `1._x` are not
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let after_dot = after_dot.chars().next();
+            let is_method_or_range = matches!(
+                after_dot,
+                Some(c) if c == '.' || c == '_' || is_xid_start(c)
+            );
+            if !is_method_or_range {
+                is_float = true;
+                index += 1;
+                if value[index..].starts_with(|c: char| c.is_ascii_digit()) {
+                    index += digits_len(&value[index..], |c| c.is_ascii_digit());
+                    index += exponent_len(&value[index..]);
+                } else {
+                    // `1.` cannot have an exponent nor a suffix
+                    return Some(Literal::Float(Number {
+                        raw: &value[..index],
+                        base,
+                        digits: &value[..index],
+                        suffix: None,
+                    }));
+                }
             }
+        } else {
+            let exponent = exponent_len(rest);
+            if exponent != 0 {
+                is_float = true;
+                index += exponent;
+            }
+        }
+        (0, index)
+    } else {
+        // as rustc, binary and octal literals eat all the decimal digits
+        // so that invalid digits are reported on the whole literal
+        let digits = match base {
+            Base::Hexadecimal => digits_len(&value[2..], |c| c.is_ascii_hexdigit()),
+            _ => digits_len(&value[2..], |c| c.is_ascii_digit()),
+        };
+        if !value[2..2 + digits].chars().any(|c| c != '_') {
+            return None;
         }
+        (2, 2 + digits)
+    };
 
-        index += char.len_utf8();
+    let suffix_end = digits_end + suffix_len(&value[digits_end..]);
+    let suffix = if suffix_end == digits_end {
+        None
+    } else {
+        Some(&value[digits_end..suffix_end])
+    };
+
+    // `1f32` is a float even without a dot
+    if base == Base::Decimal && matches!(suffix, Some("f32") | Some("f64")) {
+        is_float = true;
+    }
+
+    let number = Number {
+        raw: &value[..suffix_end],
+        base,
+        digits: &value[prefix_len..digits_end],
+        suffix,
+    };
+
+    Some(if is_float {
+        Literal::Float(number)
+    } else {
+        Literal::Integer(number)
+    })
+}
+
+/// Render the literal as it was in the source
+impl<'a> core::fmt::Display for Literal<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use Literal::*;
+        let (prefix, raw, hashes) = match self {
+            Bool(raw) => return f.write_str(raw),
+            Integer(num) | Float(num) => return f.write_str(num.raw),
+            Char(raw) => return write!(f, "'{}'", raw),
+            Byte(raw) => return write!(f, "b'{}'", raw),
+            String(raw) => ("", raw, None),
+            Bytes(raw) => ("b", raw, None),
+            CString(raw) => ("c", raw, None),
+            RawString(raw, hashes) => ("r", raw, Some(*hashes)),
+            RawBytes(raw, hashes) => ("br", raw, Some(*hashes)),
+            RawCString(raw, hashes) => ("cr", raw, Some(*hashes)),
+        };
+        let hashes = hashes.unwrap_or(0);
+        f.write_str(prefix)?;
+        (0..hashes).try_for_each(|_| f.write_str("#"))?;
+        write!(f, "\"{}\"", raw)?;
+        (0..hashes).try_for_each(|_| f.write_str("#"))
     }
-    // not closing tag
-    None
 }
 
 impl<'a> TryFrom<&'a str> for Literal<'a> {
     type Error = ();
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        if let Some(literal) = get_literal_end(value, "br#\"", "\"#") {
-            return Ok(Literal::RawBytes(literal));
+        if let Some((literal, hashes)) = get_raw_literal_end(value, "br") {
+            return Ok(Literal::RawBytes(literal, hashes));
         }
-        if let Some(literal) = get_literal_end(value, "r#\"", "\"#") {
-            return Ok(Literal::RawString(literal));
+        if let Some((literal, hashes)) = get_raw_literal_end(value, "cr") {
+            return Ok(Literal::RawCString(literal, hashes));
+        }
+        if let Some((literal, hashes)) = get_raw_literal_end(value, "r") {
+            return Ok(Literal::RawString(literal, hashes));
         }
         if let Some(literal) = get_literal_end(value, "b\"", "\"") {
             return Ok(Literal::Bytes(literal));
         }
-        if let Some(literal) = get_literal_end(value, "b'", "'") {
+        if let Some(literal) = get_literal_end(value, "c\"", "\"") {
+            return Ok(Literal::CString(literal));
+        }
+        if let Some(literal) = get_char_literal_end(value, "b'") {
             return Ok(Literal::Byte(literal));
         }
         if let Some(literal) = get_literal_end(value, "\"", "\"") {
             return Ok(Literal::String(literal));
         }
-        if let Some(literal) = get_literal_end(value, "'", "'") {
+        if let Some(literal) = get_char_literal_end(value, "'") {
             return Ok(Literal::Char(literal));
-        }   
-
-
-
-        // TODO!: integers
+        }
 
-        Err(())
+        parse_number(value).ok_or(())
     }
 }
 
@@ -97,7 +334,52 @@ mod test_literal {
         assert_eq!(Literal::try_from("b'a' less").unwrap(), Literal::Byte("a"));
         assert_eq!(Literal::try_from("\"aaaa\" less").unwrap(), Literal::String("aaaa"));
         assert_eq!(Literal::try_from("b\"bbbb\" less").unwrap(), Literal::Bytes("bbbb"));
-        assert_eq!(Literal::try_from("r#\"abcd\"# less").unwrap(), Literal::RawString("abcd"));
-        assert_eq!(Literal::try_from("br#\"ohmy\"# less").unwrap(), Literal::RawBytes("ohmy"));
+        assert_eq!(Literal::try_from("r#\"abcd\"# less").unwrap(), Literal::RawString("abcd", 1));
+        assert_eq!(Literal::try_from("br#\"ohmy\"# less").unwrap(), Literal::RawBytes("ohmy", 1));
+        assert_eq!(Literal::try_from("\"a\\\"b\" less").unwrap(), Literal::String("a\\\"b"));
+        assert_eq!(Literal::try_from("\"a\\\\\" less").unwrap(), Literal::String("a\\\\"));
+    }
+
+    #[test]
+    fn try_to_parse_raw_and_c_strings() {
+        let cases = [
+            ("r\"a\\b\" less", Literal::RawString("a\\b", 0)),
+            ("r##\"a\"#b\"## less", Literal::RawString("a\"#b", 2)),
+            ("br###\"\"##\"### less", Literal::RawBytes("\"##", 3)),
+            ("c\"hello\" less", Literal::CString("hello")),
+            ("cr#\"a\"b\"# less", Literal::RawCString("a\"b", 1)),
+        ];
+        for (source, expected) in cases {
+            let literal = Literal::try_from(source).unwrap();
+            assert_eq!(literal, expected);
+            assert_eq!(literal.len(), source.len() - " less".len());
+        }
+        assert_eq!(Literal::try_from("r#ident"), Err(()));
+        assert_eq!(Literal::try_from("r##\"unterminated\"#"), Err(()));
+    }
+
+    fn number<'a>(raw: &'a str, base: Base, digits: &'a str, suffix: Option<&'a str>) -> Number<'a> {
+        Number { raw, base, digits, suffix }
+    }
+
+    #[test]
+    fn try_to_parse_numbers() {
+        use Base::*;
+        assert_eq!(Literal::try_from("123 less").unwrap(), Literal::Integer(number("123", Decimal, "123", None)));
+        assert_eq!(Literal::try_from("1_000u64;").unwrap(), Literal::Integer(number("1_000u64", Decimal, "1_000", Some("u64"))));
+        assert_eq!(Literal::try_from("0x1F)").unwrap(), Literal::Integer(number("0x1F", Hexadecimal, "1F", None)));
+        assert_eq!(Literal::try_from("0x1f32").unwrap(), Literal::Integer(number("0x1f32", Hexadecimal, "1f32", None)));
+        assert_eq!(Literal::try_from("0o777_i32,").unwrap(), Literal::Integer(number("0o777_i32", Octal, "777_", Some("i32"))));
+        assert_eq!(Literal::try_from("0b1010_1010").unwrap(), Literal::Integer(number("0b1010_1010", Binary, "1010_1010", None)));
+        assert_eq!(Literal::try_from("2.5e-3f32 ").unwrap(), Literal::Float(number("2.5e-3f32", Decimal, "2.5e-3", Some("f32"))));
+        assert_eq!(Literal::try_from("1E10").unwrap(), Literal::Float(number("1E10", Decimal, "1E10", None)));
+        assert_eq!(Literal::try_from("1f64").unwrap(), Literal::Float(number("1f64", Decimal, "1", Some("f64"))));
+        assert_eq!(Literal::try_from("1. ").unwrap(), Literal::Float(number("1.", Decimal, "1.", None)));
+        assert_eq!(Literal::try_from("1.0.foo()").unwrap(), Literal::Float(number("1.0", Decimal, "1.0", None)));
+        assert_eq!(Literal::try_from("1.foo()").unwrap(), Literal::Integer(number("1", Decimal, "1", None)));
+        assert_eq!(Literal::try_from("1..2").unwrap(), Literal::Integer(number("1", Decimal, "1", None)));
+        assert_eq!(Literal::try_from("1.0.len()").unwrap().len(), 3);
+        assert_eq!(Literal::try_from("0x"), Err(()));
+        assert_eq!(Literal::try_from("abc"), Err(()));
     }
 }
\ No newline at end of file
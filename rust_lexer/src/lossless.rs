@@ -0,0 +1,107 @@
+use crate::{Lexer, SpannedToken, Token};
+
+/// A token together with the whitespace that precedes it, so that
+/// concatenating `leading_trivia` and `text` of all the tokens gives back
+/// the source byte for byte. Comments are kept as tokens, use
+/// [`Token::is_trivia`] to tell the plain ones from the docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LosslessToken<'a> {
+    /// The whitespace and newlines between the previous token and this one
+    pub leading_trivia: &'a str,
+    pub token: SpannedToken<'a>,
+    /// The source text of the token
+    pub text: &'a str,
+}
+
+/// Iterator over the tokens of a [`Lexer`] that keeps the whitespace.
+/// The last item is a [`Token::Empty`] holding the trailing whitespace.
+pub struct LosslessTokens<'a> {
+    lexer: Lexer<'a>,
+    done: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Iterate over the tokens keeping all the whitespace as trivia.
+    /// This enables the recovery mode so that no text is ever skipped.
+    pub fn lossless(self) -> LosslessTokens<'a> {
+        LosslessTokens {
+            lexer: self.with_recovery(),
+            done: false,
+        }
+    }
+}
+
+impl<'a> LosslessTokens<'a> {
+    /// The errors found so far
+    pub fn diagnostics(&self) -> &[crate::LexerError<'a>] {
+        self.lexer.diagnostics()
+    }
+}
+
+impl<'a> Iterator for LosslessTokens<'a> {
+    type Item = LosslessToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let source = self.lexer.source();
+        let trivia_start = self.lexer.position.byte_offset;
+        self.lexer.skip_white_space();
+        let leading_trivia = &source[trivia_start..self.lexer.position.byte_offset];
+
+        if self.lexer.remaining_text.is_empty() {
+            self.done = true;
+            return Some(LosslessToken {
+                leading_trivia,
+                token: SpannedToken {
+                    token: Token::Empty,
+                    span: self.lexer.empty_span(),
+                },
+                text: "",
+            });
+        }
+
+        // in recovery mode only the end of file is an error
+        let token = self.lexer.get_next_spanned_token().ok()?;
+        Some(LosslessToken {
+            leading_trivia,
+            text: &source[token.span.byte_range()],
+            token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_lossless {
+    use super::*;
+    use alloc::string::String;
+    #[test]
+    fn rebuild_source() {
+        let sources = [
+            "",
+            "  \n",
+            "fn main() {\r\n\t// comment\n    let x = 'a';\u{2028}/* doc */ § \"open",
+            "#[pyfunction]\npub fn f<'a>(x: &'a str) -> u64 { 0x1F }\n\n",
+        ];
+        for source in sources {
+            let mut rebuilt = String::new();
+            for token in Lexer::new(source).lossless() {
+                rebuilt.push_str(token.leading_trivia);
+                rebuilt.push_str(token.text);
+            }
+            assert_eq!(rebuilt, source);
+        }
+
+        let tokens = Lexer::new(" a  b \n").lossless().collect::<alloc::vec::Vec<_>>();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!((tokens[1].leading_trivia, tokens[1].text), ("  ", "b"));
+        assert_eq!((tokens[2].leading_trivia, &tokens[2].token.token), (" \n", &Token::Empty));
+
+        let trivia = Lexer::new("// a\n/// b\n/* c */").lossless()
+            .map(|token| token.token.token.is_trivia())
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(trivia, [true, false, true, false]);
+    }
+}
@@ -1,136 +1,179 @@
-/// Simple ring buffer to simulate a bounded queue efficently
-pub struct RingBuffer<T: Default + Clone, const BUFFER_SIZE: usize = 4096> {
-    buffer: [T; BUFFER_SIZE],
-    start: usize,
-    end: usize,
+use alloc::collections::VecDeque;
+
+/// A position of a [`Peekable`] we can go back to.
+/// Every checkpoint must be either rewound to or committed, which consumes
+/// it so that it is released only once.
+#[must_use]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// Create a "peekable" iterator where we can look at any number of future
+/// values and backtrack to a [`Checkpoint`].
+///
+/// The values are kept in a queue that holds only what was peeked, plus
+/// what was consumed after the oldest live checkpoint, so once its capacity
+/// settles no further allocation happens.
+pub struct Peekable<T, I: Iterator<Item=T>> {
+    iterator: I,
+    buffer: VecDeque<T>,
+    /// Absolute index of the first value in the buffer
+    offset: usize,
+    /// Absolute index of the next value to return
+    position: usize,
+    /// How many checkpoints were created and not yet rewound or committed
+    checkpoints: usize,
 }
 
-impl <const BUFFER_SIZE: usize, T: Default + Clone> RingBuffer<T, BUFFER_SIZE> {
-    pub fn new() -> Self {
-        let buffer = unsafe{
-            let mut buffer: [T; BUFFER_SIZE] = core::mem::MaybeUninit::uninit().assume_init();
-            for i in 0..BUFFER_SIZE {
-                buffer[i] = T::default();
-            }
-            buffer
-        };
-        Self {
-            buffer,
-            start: 0,
-            end: 0,
+impl<T, I: Iterator<Item=T>> Peekable<T, I> {
+    pub fn new(iterator: I) -> Self {
+        Peekable{
+            iterator,
+            buffer: VecDeque::new(),
+            offset: 0,
+            position: 0,
+            checkpoints: 0,
         }
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
-    }
-
-    pub fn is_full(&self) -> bool {
-        self.len() == BUFFER_SIZE
+    /// Make sure that the value at `index` positions from the current one
+    /// is buffered, returns false if the iterator ends before it
+    fn fill(&mut self, index: usize) -> bool {
+        let needed = self.position - self.offset + index + 1;
+        while self.buffer.len() < needed {
+            match self.iterator.next() {
+                Some(value) => self.buffer.push_back(value),
+                None => return false,
+            }
+        }
+        true
     }
 
-    pub fn len(&self) -> usize {
-        ((BUFFER_SIZE + self.end) - self.start) % BUFFER_SIZE
+    /// Drop the consumed values nobody can rewind to
+    fn trim(&mut self) {
+        if self.checkpoints == 0 {
+            self.buffer.drain(..self.position - self.offset);
+            self.offset = self.position;
+        }
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        if self.is_empty() {
+    /// Look at the value `index` positions after the current one, 0 is the
+    /// value that `next` would return
+    pub fn peek(&mut self, index: usize) -> Option<&T> {
+        if !self.fill(index) {
             return None;
         }
-
-        let result = &self.buffer[self.start];
-        self.start = (BUFFER_SIZE + self.start + 1) % BUFFER_SIZE; 
-
-        Some(result.clone())
+        self.buffer.get(self.position - self.offset + index)
     }
 
-    pub fn reset(&mut self) {
-        self.start = 0;
-        self.end = 0;
+    pub fn get(&mut self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.peek(index).cloned()
     }
 
-    pub fn consume(&mut self, quantity: usize) {
-        let quantity = quantity.max(self.len());
-        self.start = (BUFFER_SIZE + self.start + quantity) % BUFFER_SIZE;
-    }
-
-    pub fn push(&mut self, value: T) -> Option<()> {
-        if self.is_full() {
-            return None;
+    /// Skip `quantity` values, returns `None` if there were fewer left
+    pub fn consume(&mut self, quantity: usize) -> Option<()> {
+        if quantity == 0 {
+            return Some(());
         }
-
-        self.buffer[self.end] = value;
-        self.end = (BUFFER_SIZE + self.end + 1) % BUFFER_SIZE; 
-
-        Some(())
+        let available = self.fill(quantity - 1);
+        let buffered = self.buffer.len() - (self.position - self.offset);
+        self.position += quantity.min(buffered);
+        self.trim();
+        if available { Some(()) } else { None }
     }
 
-    pub fn get(&mut self, index: usize) -> Option<T> {
-        if index > self.len() {
-            return None;
-        }
-
-        let idx = (BUFFER_SIZE + self.start + index) % BUFFER_SIZE;
-        Some(self.buffer[idx].clone())
+    /// Remember the current position to be able to [`Peekable::rewind`] to it
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.checkpoints += 1;
+        Checkpoint(self.position)
     }
-}
 
-/// Create a "peekable" iterator where we can look at future values if present.
-pub struct Peekable<T: Default + Clone, I: Iterator<Item=T>, const BUFFER_SIZE: usize = 4096> {
-    iterator: I,
-    cache: RingBuffer<T, BUFFER_SIZE>,
-}
+    /// Go back to the position of the checkpoint
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        assert!(
+            self.checkpoints > 0 && checkpoint.0 >= self.offset,
+            "Rewinding to a checkpoint that was already released"
+        );
+        self.position = checkpoint.0;
+        self.checkpoints -= 1;
+        self.trim();
+    }
 
-impl<T: Default + Clone, I: Iterator<Item=T>, const BUFFER_SIZE: usize> Peekable<T, I, BUFFER_SIZE> {
-    pub fn new(iterator: I) -> Self {
-            Peekable{
-            iterator,
-            cache: RingBuffer::new(),
-        }
+    /// Release the checkpoint keeping the current position
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        assert!(
+            self.checkpoints > 0 && checkpoint.0 >= self.offset,
+            "Committing a checkpoint that was already released"
+        );
+        self.checkpoints -= 1;
+        self.trim();
     }
+}
 
-    pub fn get(&mut self, index: usize) -> Option<T> {
-        // TODO!: how to handle gracefully this?
-        if index > BUFFER_SIZE {
-            panic!("Cannot peek at index '{}' because the buffer size is '{}'", index, BUFFER_SIZE);
-        }
+impl<T: Clone, I: Iterator<Item=T>> Iterator for Peekable<T, I> {
+    type Item = T;
 
-        // if needed fill the cache
-        if self.cache.len() < index {
-            for _ in 0..index - self.cache.len() {
-                if let Some(value) = self.iterator.next() {
-                    self.cache.push(value).unwrap();
-                } else {
-                    // the iterator ended, so we cannot get that value
-                    return None;
-                }
-            }
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position - self.offset == self.buffer.len() && self.checkpoints == 0 {
+            // nothing is buffered and nobody can rewind, skip the queue
+            let value = self.iterator.next()?;
+            self.position += 1;
+            self.offset += 1;
+            return Some(value);
         }
 
-        self.cache.get(index)
-    }
-
-    pub fn consume(&mut self, quantity: usize) -> Option<()> {
-        let reminder = quantity.saturating_sub(self.cache.len());
-        let cache_to_remove = quantity - reminder;
-        
-        self.cache.consume(cache_to_remove);
-        for _ in 0..reminder {
-            self.iterator.next()?;
+        if !self.fill(0) {
+            return None;
         }
 
-        Some(())
+        let value = if self.checkpoints == 0 {
+            self.offset += 1;
+            self.buffer.pop_front()
+        } else {
+            self.buffer.get(self.position - self.offset).cloned()
+        };
+        self.position += 1;
+        value
     }
 }
 
-impl<T: Default + Clone, I: Iterator<Item=T>, const BUFFER_SIZE: usize> Iterator for Peekable<T, I, BUFFER_SIZE> {
-    type Item = T;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.cache.is_empty() {
-            self.iterator.next()
-        } else {
-            Some(self.cache.pop().unwrap()) // Defensive unwrap
-        }
+#[cfg(test)]
+mod test_peekable {
+    use super::*;
+    use alloc::vec::Vec;
+    #[test]
+    fn peek_and_backtrack() {
+        let mut stream = Peekable::new(0..10_000);
+        assert_eq!(stream.get(0), Some(0));
+        assert_eq!(stream.get(5000), Some(5000));
+        assert_eq!(stream.next(), Some(0));
+        assert_eq!(stream.consume(2), Some(()));
+        assert_eq!(stream.peek(0), Some(&3));
+
+        let outer = stream.checkpoint();
+        assert_eq!(stream.next(), Some(3));
+        let inner = stream.checkpoint();
+        stream.consume(10);
+        assert_eq!(stream.next(), Some(14));
+        stream.rewind(inner);
+        assert_eq!(stream.next(), Some(4));
+        stream.rewind(outer);
+        assert_eq!(stream.next(), Some(3));
+
+        let checkpoint = stream.checkpoint();
+        stream.consume(100);
+        stream.commit(checkpoint);
+        assert_eq!(stream.next(), Some(104));
+        // only what is still peekable is kept around
+        assert_eq!(stream.buffer.len(), 5000 - 104);
+
+        assert_eq!(stream.consume(20_000), None);
+        assert_eq!(stream.get(0), None);
+        assert_eq!(stream.by_ref().count(), 0);
+
+        let values = Peekable::new(0..5).collect::<Vec<_>>();
+        assert_eq!(values, [0, 1, 2, 3, 4]);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,349 @@
+//! Conversion between our tokens and `proc_macro2` ones, so that `syn` and
+//! `quote!` can be used on top of this lexer.
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::str::FromStr;
+use proc_macro2::{Delimiter as PmDelimiter, Group as PmGroup, Ident, Literal as PmLiteral, Punct, Spacing, TokenStream, TokenTree as PmTokenTree};
+use crate::{Comment, Delimiter, Identifier, Keyword, LexerConfig, Lifetime, Literal, Position, Span, SpannedToken, Symbol, Token, TokenTree};
+
+#[derive(Debug)]
+pub enum InteropError {
+    /// A token that has no `proc_macro2` equivalent, such as an error
+    /// token or the byte order mark
+    UnsupportedToken(Span),
+    /// A `proc_macro2` token whose span does not match a token of the source,
+    /// the position is the line and column of `proc_macro2` (1-based lines)
+    NotInSource {
+        line: usize,
+        column: usize,
+    },
+    /// `proc_macro2` could not tokenize the source
+    ProcMacro2(proc_macro2::LexError),
+}
+
+impl From<Delimiter> for PmDelimiter {
+    fn from(value: Delimiter) -> Self {
+        match value {
+            Delimiter::Parenthesis => PmDelimiter::Parenthesis,
+            Delimiter::Bracket => PmDelimiter::Bracket,
+            Delimiter::Brace => PmDelimiter::Brace,
+        }
+    }
+}
+
+/// Push the chars of a symbol as joint puncts, the last one is joint too
+/// if the symbol is glued to the next one, as in `&'a`
+fn push_puncts(output: &mut Vec<PmTokenTree>, text: &str, is_glued: bool) {
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        let spacing = if chars.peek().is_some() || is_glued { Spacing::Joint } else { Spacing::Alone };
+        output.push(Punct::new(char, spacing).into());
+    }
+}
+
+fn push_token(output: &mut Vec<PmTokenTree>, token: &SpannedToken<'_>, is_glued: bool) -> Result<(), InteropError> {
+    match &token.token {
+        Token::Identifier(identifier) if identifier.is_raw() => {
+            output.push(Ident::new_raw(identifier.name(), proc_macro2::Span::call_site()).into());
+        },
+        Token::Identifier(identifier) => {
+            output.push(Ident::new(identifier.name(), proc_macro2::Span::call_site()).into());
+        },
+        Token::Keyword(keyword) => {
+            output.push(Ident::new((*keyword).into(), proc_macro2::Span::call_site()).into());
+        },
+        Token::Symbol(Symbol::Underscore) => {
+            output.push(Ident::new("_", proc_macro2::Span::call_site()).into());
+        },
+        Token::Symbol(Symbol::Utf8Bom) => return Err(InteropError::UnsupportedToken(token.span)),
+        Token::Symbol(symbol) => push_puncts(output, (*symbol).into(), is_glued),
+        Token::Lifetime(lifetime) => {
+            output.push(Punct::new('\'', Spacing::Joint).into());
+            let ident = match lifetime.name().strip_prefix("r#") {
+                Some(name) => Ident::new_raw(name, proc_macro2::Span::call_site()),
+                None => Ident::new(lifetime.name(), proc_macro2::Span::call_site()),
+            };
+            output.push(ident.into());
+        },
+        Token::Literal(Literal::Bool(raw)) => {
+            output.push(Ident::new(raw, proc_macro2::Span::call_site()).into());
+        },
+        Token::Literal(literal) => {
+            let literal = PmLiteral::from_str(&literal.to_string())
+                .map_err(|_| InteropError::UnsupportedToken(token.span))?;
+            output.push(literal.into());
+        },
+        // doc comments are attributes, as `proc_macro2` and rustc do
+        Token::Comment(comment) if comment.is_doc() => {
+            let (text, is_inner) = match comment {
+                Comment::InnerLineDoc(text) | Comment::InnerBlockDoc(text) => (text, true),
+                Comment::OuterLineDoc(text) | Comment::OuterBlockDoc(text) => (text, false),
+                _ => unreachable!(),
+            };
+            output.push(Punct::new('#', Spacing::Alone).into());
+            if is_inner {
+                output.push(Punct::new('!', Spacing::Alone).into());
+            }
+            let attribute = [
+                PmTokenTree::from(Ident::new("doc", proc_macro2::Span::call_site())),
+                Punct::new('=', Spacing::Alone).into(),
+                PmLiteral::string(text).into(),
+            ];
+            output.push(PmGroup::new(PmDelimiter::Bracket, attribute.into_iter().collect()).into());
+        },
+        Token::Comment(_) | Token::Empty => {},
+        Token::Error { span, .. } => return Err(InteropError::UnsupportedToken(*span)),
+    }
+    Ok(())
+}
+
+fn push_trees(output: &mut Vec<PmTokenTree>, trees: &[TokenTree<'_>]) -> Result<(), InteropError> {
+    for (index, tree) in trees.iter().enumerate() {
+        match tree {
+            TokenTree::Leaf(token) => {
+                let is_glued = match trees.get(index + 1) {
+                    Some(TokenTree::Leaf(next)) => {
+                        next.span.start == token.span.end
+                        && matches!(next.token, Token::Lifetime(_) | Token::Symbol(_))
+                        && next.token != Token::Symbol(Symbol::Underscore)
+                    },
+                    _ => false,
+                };
+                push_token(output, token, is_glued)?
+            },
+            TokenTree::Group(group) => {
+                let mut children = Vec::new();
+                push_trees(&mut children, &group.children)?;
+                output.push(PmGroup::new(group.delimiter.into(), children.into_iter().collect()).into());
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Convert token trees to a `proc_macro2` stream. Outside of a procedural
+/// macro `proc_macro2` cannot create spans at arbitrary positions, so the
+/// tokens get `Span::call_site()`, use [`from_token_stream`] to go back
+/// to our spans.
+pub fn to_token_stream(trees: &[TokenTree<'_>]) -> Result<TokenStream, InteropError> {
+    let mut output = Vec::new();
+    push_trees(&mut output, trees)?;
+    Ok(output.into_iter().collect())
+}
+
+/// Maps the line and columns of `proc_macro2` to byte offsets
+struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+    /// The last position found, the tokens are mostly looked up in order so
+    /// the next one is searched from there and not from its line start,
+    /// which is quadratic on long lines
+    last: Cell<Position>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut line_starts = Vec::from([0]);
+        line_starts.extend(source.match_indices('\n').map(|(index, _)| index + 1));
+        LineIndex { source, line_starts, last: Cell::new(Position::default()) }
+    }
+
+    fn position(&self, location: proc_macro2::LineColumn) -> Result<Position, InteropError> {
+        let not_in_source = InteropError::NotInSource { line: location.line, column: location.column };
+        let line_start = *location.line.checked_sub(1)
+            .and_then(|line| self.line_starts.get(line))
+            .ok_or(not_in_source)?;
+        let last = self.last.get();
+        let (from_offset, from_column) = if last.line + 1 == location.line && last.column <= location.column {
+            (last.byte_offset, last.column)
+        } else {
+            (line_start, 0)
+        };
+        let byte_offset = self.source[from_offset..].char_indices()
+            .nth(location.column - from_column)
+            .map(|(offset, _)| from_offset + offset)
+            .unwrap_or(self.source.len());
+        let position = Position {
+            byte_offset,
+            line: location.line - 1,
+            column: location.column,
+        };
+        self.last.set(position);
+        Ok(position)
+    }
+
+    fn span(&self, span: proc_macro2::Span, file_id: usize) -> Result<Span, InteropError> {
+        Ok(Span {
+            file_id,
+            start: self.position(span.start())?,
+            end: self.position(span.end())?,
+        })
+    }
+}
+
+/// Convert a stream that `proc_macro2` parsed from `source` (with the
+/// `span-locations` feature) back to our tokens, which borrow `source`.
+/// Tokens follow the conventions of our lexer: doc comments are comments,
+/// puncts are merged in symbols and `()` is [`Symbol::EmptyTuple`]. The
+/// keywords are the ones of the edition of `config`.
+pub fn from_token_stream<'a>(source: &'a str, file_id: usize, config: LexerConfig, stream: TokenStream) -> Result<Vec<SpannedToken<'a>>, InteropError> {
+    let index = LineIndex::new(source);
+    let mut result = Vec::new();
+    push_from_stream(&index, file_id, config, stream, &mut result)?;
+    Ok(result)
+}
+
+/// Tokenize `source` with `proc_macro2` and convert the result to our tokens
+pub fn parse_with_proc_macro2(source: &str, file_id: usize, config: LexerConfig) -> Result<Vec<SpannedToken<'_>>, InteropError> {
+    let stream = TokenStream::from_str(source).map_err(InteropError::ProcMacro2)?;
+    from_token_stream(source, file_id, config, stream)
+}
+
+/// Convert the trees, taking the kind, the text and the span of each token
+/// from `proc_macro2`. Our lexer only reads the text `proc_macro2` gives a
+/// token, which must be exactly one token of the same kind, so that a token
+/// of ours that is too long or too short can't hide in the result.
+fn push_from_stream<'a>(index: &LineIndex<'a>, file_id: usize, config: LexerConfig, stream: TokenStream, result: &mut Vec<SpannedToken<'a>>) -> Result<(), InteropError> {
+    let source = index.source;
+    let mut trees = stream.into_iter().peekable();
+    while let Some(tree) = trees.next() {
+        let span = index.span(tree.span(), file_id)?;
+        let text = &source[span.byte_range()];
+        let not_in_source = || InteropError::NotInSource { line: span.start.line + 1, column: span.start.column };
+
+        let token = match &tree {
+            PmTokenTree::Group(group) => {
+                let open = index.span(group.span_open(), file_id)?;
+                let close = index.span(group.span_close(), file_id)?;
+                let delimiter = match group.delimiter() {
+                    PmDelimiter::Parenthesis => Delimiter::Parenthesis,
+                    PmDelimiter::Bracket => Delimiter::Bracket,
+                    PmDelimiter::Brace => Delimiter::Brace,
+                    PmDelimiter::None => {
+                        push_from_stream(index, file_id, config, group.stream(), result)?;
+                        continue;
+                    },
+                };
+                if delimiter == Delimiter::Parenthesis && open.end == close.start {
+                    result.push(SpannedToken {
+                        token: Token::Symbol(Symbol::EmptyTuple),
+                        span: open.join(&close),
+                    });
+                    continue;
+                }
+                result.push(SpannedToken { token: Token::Symbol(delimiter.open_symbol()), span: open });
+                push_from_stream(index, file_id, config, group.stream(), result)?;
+                result.push(SpannedToken { token: Token::Symbol(delimiter.close_symbol()), span: close });
+                continue;
+            },
+            PmTokenTree::Ident(_) => {
+                let identifier = Identifier::try_from(text).map_err(|_| not_in_source())?;
+                if identifier.0 == "_" {
+                    Token::Symbol(Symbol::Underscore)
+                } else if let Some(keyword) = Keyword::classify(identifier, config.edition) {
+                    Token::Keyword(keyword)
+                } else {
+                    Token::Identifier(identifier)
+                }
+            },
+            PmTokenTree::Literal(_) => {
+                Token::Literal(Literal::try_from(text).map_err(|_| not_in_source())?)
+            },
+            // a doc comment is `#`, `!` for inner ones and `[doc = "..."]`,
+            // all with the span of the comment
+            PmTokenTree::Punct(punct) if punct.as_char() == '#' && text.starts_with('/') => {
+                let comment = Comment::try_from(text).map_err(|_| not_in_source())?;
+                while trees.peek().is_some_and(|next| index.span(next.span(), file_id).is_ok_and(|next| next == span)) {
+                    trees.next();
+                }
+                Token::Comment(comment)
+            },
+            // a lifetime is a joint `'` and an identifier
+            PmTokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                let Some(PmTokenTree::Ident(name)) = trees.next() else {
+                    return Err(not_in_source());
+                };
+                let span = span.join(&index.span(name.span(), file_id)?);
+                let text = &source[span.byte_range()];
+                let lifetime = Lifetime::try_from(text).map_err(|_| not_in_source())?;
+                push_checked(result, Token::Lifetime(lifetime), span, text.len()).ok_or_else(not_in_source)?;
+                continue;
+            },
+            PmTokenTree::Punct(punct) => {
+                // the puncts joint to the next one make symbols with it, as
+                // `+=` for `+` and `=`, but not with the `'` of a lifetime
+                let mut end = span;
+                let mut spacing = punct.spacing();
+                while spacing == Spacing::Joint {
+                    let Some(PmTokenTree::Punct(next)) = trees.peek() else { break };
+                    if next.as_char() == '\'' {
+                        break;
+                    }
+                    spacing = next.spacing();
+                    end = index.span(next.span(), file_id)?;
+                    trees.next();
+                }
+                // the run is cut in the longest symbols, as `+=-` in `+=` and `-`
+                let mut start = span.start;
+                let run_end = end.end.byte_offset;
+                while start.byte_offset < run_end {
+                    let text = &source[start.byte_offset..run_end];
+                    let symbol = Symbol::try_from(text).map_err(|_| not_in_source())?;
+                    let mut symbol_end = start;
+                    symbol_end.advance(&text[..symbol.len()]);
+                    result.push(SpannedToken { token: Token::Symbol(symbol), span: Span { start, end: symbol_end, file_id } });
+                    start = symbol_end;
+                }
+                continue;
+            },
+        };
+        push_checked(result, token, span, text.len()).ok_or_else(not_in_source)?;
+    }
+    Ok(())
+}
+
+/// Push the token read from the text of a `proc_macro2` token, `None` if
+/// it's not the whole text
+fn push_checked<'a>(result: &mut Vec<SpannedToken<'a>>, token: Token<'a>, span: Span, len: usize) -> Option<()> {
+    if token.len() != len {
+        return None;
+    }
+    result.push(SpannedToken { token, span });
+    Some(())
+}
+
+#[cfg(test)]
+mod test_proc_macro2_interop {
+    use super::*;
+    use crate::{Edition, Lexer};
+    #[test]
+    fn round_trip() {
+        let source = "/// doc\n#[cfg(any(a, b))]\npub fn f<'a>(x: &'a str, r#type: u8) -> Vec<()> {\n    x == \"é\" && 1.5e3 >= 0x1F as f64; b'a'; r#\"raw\"#; _\n}\n";
+
+        let ours = Lexer::new(source).spanned().collect::<Vec<_>>();
+        let trees = TokenTree::from_tokens(ours.iter().cloned()).unwrap();
+        let stream = to_token_stream(&trees).unwrap();
+        assert_eq!(stream.to_string(), TokenStream::from_str(source).unwrap().to_string());
+
+        let theirs = parse_with_proc_macro2(source, 0, LexerConfig::default()).unwrap();
+        assert_eq!(ours, theirs);
+        let config = LexerConfig { edition: Edition::Edition2015 };
+        let old = parse_with_proc_macro2("async fn f() {}", 0, config).unwrap();
+        assert_eq!(old, Lexer::new("async fn f() {}").with_config(config).spanned().collect::<Vec<_>>());
+        assert!(matches!(old[0].token, Token::Identifier(_)));
+        assert!(theirs.iter().any(|token| token.token == Token::Comment(Comment::OuterLineDoc(" doc"))));
+
+        let error = Lexer::new("§").with_recovery().spanned().collect::<Vec<_>>();
+        let trees = TokenTree::from_tokens(error).unwrap();
+        assert!(matches!(to_token_stream(&trees), Err(InteropError::UnsupportedToken(_))));
+        assert!(matches!(parse_with_proc_macro2("(", 0, LexerConfig::default()), Err(InteropError::ProcMacro2(_))));
+        assert_eq!(Literal::RawBytes("a", 2).to_string(), "br##\"a\"##");
+
+        // `Ident::new` panics on `r#a`
+        let raw = Lexer::new("&'r#a u8").spanned().collect::<Vec<_>>();
+        assert!(matches!(raw[1].token, Token::Lifetime(lifetime) if lifetime.name() == "r#a"));
+        let stream = to_token_stream(&TokenTree::from_tokens(raw).unwrap()).unwrap();
+        assert_eq!(stream.to_string(), TokenStream::from_str("&'r#a u8").unwrap().to_string());
+    }
+}
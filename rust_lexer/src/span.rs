@@ -0,0 +1,100 @@
+use core::ops::Range;
+use crate::Token;
+
+/// A point in a source file. Lines and columns start from 0 and columns
+/// count chars, not bytes, so they match what editors show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub byte_offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Move the position past `text`, which must be the text that follows it
+    pub fn advance(&mut self, text: &str) {
+        for char in text.chars() {
+            if char == '\n' {
+                self.line += 1;
+                self.column = 0;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.byte_offset += text.len();
+    }
+}
+
+/// The region of a source file covered by a token, `end` is exclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// Identifier of the source file, chosen by the user of the lexer
+    pub file_id: usize,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn len(&self) -> usize {
+        self.end.byte_offset - self.start.byte_offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.byte_offset..self.end.byte_offset
+    }
+
+    /// The smallest span that covers both `self` and `other`
+    pub fn join(&self, other: &Span) -> Span {
+        Span {
+            file_id: self.file_id,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+/// A token and where it was found
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SpannedToken<'a> {
+    pub token: Token<'a>,
+    pub span: Span,
+}
+
+#[cfg(test)]
+mod test_span {
+    use super::*;
+    use crate::{Lexer, Symbol};
+    #[test]
+    fn spans_of_tokens() {
+        let source = "fn\u{2028}é(\n  /* a\nb */ x)";
+        let spans = Lexer::new(source).with_file_id(3).spanned()
+            .map(|token| (token.span.start.line, token.span.start.column, &source[token.span.byte_range()]))
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(spans, [
+            (0, 0, "fn"),
+            (0, 3, "é"),
+            (0, 4, "("),
+            (1, 2, "/* a\nb */"),
+            (2, 5, "x"),
+            (2, 6, ")"),
+        ]);
+
+        let mut lexer = Lexer::new("a // comment").with_file_id(3);
+        lexer.get_next_spanned_token().unwrap();
+        let comment = lexer.get_next_spanned_token().unwrap();
+        assert_eq!(comment.span, Span {
+            file_id: 3,
+            start: Position { byte_offset: 2, line: 0, column: 2 },
+            end: Position { byte_offset: 12, line: 0, column: 12 },
+        });
+        assert!(lexer.get_next_spanned_token().is_err());
+
+        let open = Lexer::new(" (").spanned().next().unwrap();
+        assert_eq!(open.token, Token::Symbol(Symbol::OpenParenthesis));
+        assert_eq!(open.span.join(&comment.span).byte_range(), 1..12);
+    }
+}
@@ -0,0 +1,305 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+use crate::{offset_in, Lexer, LexerConfig, Position, Span, Token};
+
+/// How many bytes after the end of a token the lexer may look at to decide
+/// where it ends, e.g. `1.f` against `1.5`. A token closer than this to the
+/// end of the buffered text waits for more input.
+const LOOKAHEAD_BYTES: usize = 16;
+
+/// A token that owns its text, as yielded by the [`ChunkedLexer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedToken {
+    /// The source text of the token
+    pub text: String,
+    pub span: Span,
+    /// The token with its slices left empty
+    shape: Token<'static>,
+    /// Where the slices of the token are in `text`
+    ranges: Vec<Range<usize>>,
+}
+
+impl OwnedToken {
+    fn new(text: &str, token: &Token<'_>, span: Span) -> Self {
+        let mut ranges = Vec::new();
+        let shape = token.map_strs(|value| {
+            // Defensive unwrap, the slices of a token are in its text
+            let start = offset_in(text, value).unwrap();
+            ranges.push(start..start + value.len());
+            ""
+        });
+        Self { text: String::from(text), span, shape, ranges }
+    }
+
+    /// The token, borrowing from the text
+    pub fn token(&self) -> Token<'_> {
+        let mut ranges = self.ranges.iter();
+        self.shape.map_strs(|_| ranges.next().and_then(|range| self.text.get(range.clone())).unwrap_or(""))
+    }
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    /// The bytes starting at this offset of the input are not UTF-8
+    InvalidUtf8 { byte_offset: usize },
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+/// A lexer that is fed the source a chunk at a time, it only keeps the text
+/// of the token being lexed so its memory doesn't grow with the source.
+///
+/// The errors are always recovered from, as with [`Lexer::with_recovery`],
+/// and yielded as [`Token::Error`] tokens.
+pub struct ChunkedLexer {
+    /// The text fed and not yet lexed, after `consumed`
+    buffer: String,
+    consumed: usize,
+    /// The start of a char whose remaining bytes are in the next chunk
+    incomplete: Vec<u8>,
+    /// The position of the first byte after `consumed`
+    position: Position,
+    /// How many bytes were fed, including the incomplete ones
+    fed: usize,
+    /// How long the text after `consumed` must be before lexing again a
+    /// token that went up to the end of the buffer
+    retry_len: usize,
+    file_id: usize,
+    config: LexerConfig,
+    is_finished: bool,
+}
+
+impl Default for ChunkedLexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkedLexer {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::new(),
+            consumed: 0,
+            incomplete: Vec::new(),
+            position: Position::default(),
+            fed: 0,
+            retry_len: 0,
+            file_id: 0,
+            config: LexerConfig::default(),
+            is_finished: false,
+        }
+    }
+
+    /// Set the file id reported in the spans of the tokens
+    pub fn with_file_id(mut self, file_id: usize) -> Self {
+        self.file_id = file_id;
+        self
+    }
+
+    /// Lex following the given settings, e.g. the keywords of an edition
+    pub fn with_config(mut self, config: LexerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Add the next chunk of the source, a char can be split between two
+    /// chunks
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), StreamError> {
+        let start = self.fed - self.incomplete.len();
+        self.fed += chunk.len();
+        let joined;
+        let bytes = if self.incomplete.is_empty() {
+            chunk
+        } else {
+            joined = [core::mem::take(&mut self.incomplete).as_slice(), chunk].concat();
+            joined.as_slice()
+        };
+
+        let (valid, rest) = match core::str::from_utf8(bytes) {
+            Ok(text) => (text, &[][..]),
+            // the chunk ends in the middle of a char
+            Err(error) if error.error_len().is_none() => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                // Defensive unwrap, the bytes were just validated
+                (core::str::from_utf8(valid).unwrap(), rest)
+            },
+            Err(error) => return Err(StreamError::InvalidUtf8 { byte_offset: start + error.valid_up_to() }),
+        };
+
+        // drop the lexed text once it is most of the buffer, so that the
+        // copy is amortized
+        if self.consumed > self.buffer.len() / 2 {
+            self.buffer.drain(..self.consumed);
+            self.consumed = 0;
+        }
+        self.buffer.push_str(valid);
+        self.incomplete.extend_from_slice(rest);
+        Ok(())
+    }
+
+    /// Tell that there is no more input, so the last token can be lexed
+    pub fn finish(&mut self) -> Result<(), StreamError> {
+        self.is_finished = true;
+        if self.incomplete.is_empty() {
+            Ok(())
+        } else {
+            Err(StreamError::InvalidUtf8 { byte_offset: self.fed - self.incomplete.len() })
+        }
+    }
+
+    /// The next token, `None` if more input is needed or, once finished,
+    /// if there are no more tokens
+    pub fn next_token(&mut self) -> Option<OwnedToken> {
+        let text = &self.buffer[self.consumed..];
+        // a long token fed in small chunks is lexed again only once the text
+        // doubled, so it's lexed a logarithmic number of times
+        if !self.is_finished && text.len() < self.retry_len {
+            return None;
+        }
+        let mut lexer = Lexer::new(text)
+            .with_file_id(self.file_id)
+            .with_recovery()
+            .with_config(self.config);
+        lexer.position = self.position;
+
+        let token = lexer.next_spanned();
+        let end = match &token {
+            Some(token) => token.span.end,
+            // only whitespace is left, it cannot be part of a token
+            None => lexer.position,
+        };
+        let len = end.byte_offset - self.position.byte_offset;
+        if !self.is_finished && (token.is_none() || len + LOOKAHEAD_BYTES > text.len()) {
+            // the token may go on in the next chunk
+            if token.is_none() {
+                self.consumed += len;
+                self.position = end;
+            } else {
+                self.retry_len = 2 * text.len();
+            }
+            return None;
+        }
+
+        let token = token?;
+        let owned = OwnedToken::new(
+            &text[token.span.start.byte_offset - self.position.byte_offset..len],
+            &token.token,
+            token.span,
+        );
+        self.retry_len = 0;
+        self.consumed += len;
+        self.position = end;
+        Some(owned)
+    }
+}
+
+/// Lex a source read a chunk at a time, see [`ChunkedLexer`]
+#[cfg(feature = "std")]
+pub struct StreamingLexer<R: std::io::Read> {
+    reader: R,
+    lexer: ChunkedLexer,
+    chunk: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> StreamingLexer<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_lexer(reader, ChunkedLexer::new())
+    }
+
+    /// Read with a lexer already configured
+    pub fn with_lexer(reader: R, lexer: ChunkedLexer) -> Self {
+        Self {
+            reader,
+            lexer,
+            chunk: alloc::vec![0; 64 * 1024],
+        }
+    }
+
+    /// Set how many bytes are read at a time
+    pub fn with_chunk_size(mut self, size: usize) -> Self {
+        self.chunk.resize(size.max(1), 0);
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> Iterator for StreamingLexer<R> {
+    type Item = Result<OwnedToken, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.lexer.next_token() {
+                return Some(Ok(token));
+            }
+            if self.lexer.is_finished {
+                return None;
+            }
+            let read = match self.reader.read(&mut self.chunk) {
+                Ok(read) => read,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(error) => return Some(Err(StreamError::Io(error))),
+            };
+            let result = if read == 0 {
+                self.lexer.finish()
+            } else {
+                self.lexer.feed(&self.chunk[..read])
+            };
+            if let Err(error) = result {
+                // don't yield the same error forever
+                self.lexer.is_finished = true;
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_streaming {
+    use super::*;
+    #[test]
+    fn lex_split_chunks() {
+        let source = "fn é() -> &'static str { /* a\n b */ \"ü\\n\" } 1.f 1.5 § 'x'";
+        let expected = Lexer::new(source).with_recovery().spanned().collect::<Vec<_>>();
+        for chunk_size in [1, 2, 3, 7, 100] {
+            let mut lexer = ChunkedLexer::new();
+            let mut tokens = Vec::new();
+            for chunk in source.as_bytes().chunks(chunk_size) {
+                lexer.feed(chunk).unwrap();
+                tokens.extend(core::iter::from_fn(|| lexer.next_token()));
+            }
+            lexer.finish().unwrap();
+            tokens.extend(core::iter::from_fn(|| lexer.next_token()));
+
+            let tokens = tokens.iter().map(|token| (token.token(), token.span)).collect::<Vec<_>>();
+            let expected = expected.iter().map(|token| (token.token.clone(), token.span)).collect::<Vec<_>>();
+            assert_eq!(tokens, expected, "chunks of {} bytes", chunk_size);
+        }
+
+        #[cfg(feature = "std")]
+        {
+            let tokens = StreamingLexer::new(source.as_bytes())
+                .with_chunk_size(5)
+                .map(|token| token.unwrap().span)
+                .collect::<Vec<_>>();
+            assert_eq!(tokens, expected.iter().map(|token| token.span).collect::<Vec<_>>());
+        }
+
+        // a comment longer than the buffer fed a byte at a time
+        let source = alloc::format!("/*{}*/ a", "x".repeat(1 << 16));
+        let mut lexer = ChunkedLexer::new();
+        let mut tokens = Vec::new();
+        for chunk in source.as_bytes().chunks(1) {
+            lexer.feed(chunk).unwrap();
+            tokens.extend(core::iter::from_fn(|| lexer.next_token()));
+        }
+        lexer.finish().unwrap();
+        tokens.extend(core::iter::from_fn(|| lexer.next_token()));
+        assert_eq!(tokens.iter().map(|token| token.text.len()).collect::<Vec<_>>(), [source.len() - 2, 1]);
+
+        let mut lexer = ChunkedLexer::new();
+        lexer.feed(&[b'a', 0xC3]).unwrap();
+        assert!(matches!(lexer.feed(b"b"), Err(StreamError::InvalidUtf8 { byte_offset: 1 })));
+    }
+}
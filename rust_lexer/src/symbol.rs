@@ -28,6 +28,12 @@ impl From<Symbol> for &'static str {
     }
 }
 
+impl core::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str((*self).into())
+    }
+}
+
 impl<'a> TryFrom<&'a str> for Symbol {
     type Error = ();
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
@@ -0,0 +1,236 @@
+use alloc::vec::Vec;
+use crate::{Position, Span, SpannedToken, Symbol, Token};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    Parenthesis, // ( )
+    Bracket,     // [ ]
+    Brace,       // { }
+}
+
+impl Delimiter {
+    pub fn open_symbol(&self) -> Symbol {
+        match self {
+            Delimiter::Parenthesis => Symbol::OpenParenthesis,
+            Delimiter::Bracket => Symbol::OpenBraket,
+            Delimiter::Brace => Symbol::OpenBraces,
+        }
+    }
+
+    pub fn close_symbol(&self) -> Symbol {
+        match self {
+            Delimiter::Parenthesis => Symbol::CloseParenthesis,
+            Delimiter::Bracket => Symbol::CloseBraket,
+            Delimiter::Brace => Symbol::CloseBraces,
+        }
+    }
+
+    pub fn from_open(symbol: Symbol) -> Option<Self> {
+        match symbol {
+            Symbol::OpenParenthesis => Some(Delimiter::Parenthesis),
+            Symbol::OpenBraket => Some(Delimiter::Bracket),
+            Symbol::OpenBraces => Some(Delimiter::Brace),
+            _ => None,
+        }
+    }
+
+    pub fn from_close(symbol: Symbol) -> Option<Self> {
+        match symbol {
+            Symbol::CloseParenthesis => Some(Delimiter::Parenthesis),
+            Symbol::CloseBraket => Some(Delimiter::Bracket),
+            Symbol::CloseBraces => Some(Delimiter::Brace),
+            _ => None,
+        }
+    }
+}
+
+/// A delimited sequence of token trees
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group<'a> {
+    pub delimiter: Delimiter,
+    /// Span of the opening delimiter
+    pub open: Span,
+    /// Span of the closing delimiter
+    pub close: Span,
+    pub children: Vec<TokenTree<'a>>,
+}
+
+impl<'a> Group<'a> {
+    /// Span from the opening to the closing delimiter, both included
+    pub fn span(&self) -> Span {
+        self.open.join(&self.close)
+    }
+
+    /// Span of the content between the delimiters
+    pub fn inner_span(&self) -> Span {
+        Span {
+            file_id: self.open.file_id,
+            start: self.open.end,
+            end: self.close.start,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenTree<'a> {
+    Leaf(SpannedToken<'a>),
+    Group(Group<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenTreeError {
+    /// e.g. the `]` in `(a]`
+    Mismatched {
+        expected: Delimiter,
+        found: Delimiter,
+        open: Span,
+        close: Span,
+    },
+    /// An opening delimiter without its closing one
+    Unclosed {
+        delimiter: Delimiter,
+        open: Span,
+    },
+    /// A closing delimiter without its opening one
+    UnexpectedClose {
+        delimiter: Delimiter,
+        close: Span,
+    },
+}
+
+/// Split the span of `()` in the spans of the two parenthesis
+fn split_empty_tuple(span: Span) -> (Span, Span) {
+    let middle = Position {
+        byte_offset: span.start.byte_offset + 1,
+        line: span.start.line,
+        column: span.start.column + 1,
+    };
+    (
+        Span { end: middle, ..span },
+        Span { start: middle, ..span },
+    )
+}
+
+impl<'a> TokenTree<'a> {
+    pub fn span(&self) -> Span {
+        match self {
+            TokenTree::Leaf(token) => token.span,
+            TokenTree::Group(group) => group.span(),
+        }
+    }
+
+    /// Group the tokens by their delimiters. The `()` symbol becomes an
+    /// empty parenthesized group.
+    pub fn from_tokens<I>(tokens: I) -> Result<Vec<TokenTree<'a>>, TokenTreeError>
+    where
+        I: IntoIterator<Item=SpannedToken<'a>>,
+    {
+        // the groups being built, with the top level one at the bottom
+        let mut stack: Vec<(Delimiter, Span, Vec<TokenTree<'a>>)> = Vec::new();
+        let mut top_level = Vec::new();
+
+        for token in tokens {
+            let symbol = match token.token {
+                Token::Symbol(symbol) => symbol,
+                _ => {
+                    stack.last_mut().map(|(_, _, children)| children).unwrap_or(&mut top_level)
+                        .push(TokenTree::Leaf(token));
+                    continue;
+                }
+            };
+
+            let tree = if symbol == Symbol::EmptyTuple {
+                let (open, close) = split_empty_tuple(token.span);
+                TokenTree::Group(Group {
+                    delimiter: Delimiter::Parenthesis,
+                    open,
+                    close,
+                    children: Vec::new(),
+                })
+            } else if let Some(delimiter) = Delimiter::from_open(symbol) {
+                stack.push((delimiter, token.span, Vec::new()));
+                continue;
+            } else if let Some(delimiter) = Delimiter::from_close(symbol) {
+                let (expected, open, children) = stack.pop().ok_or(
+                    TokenTreeError::UnexpectedClose { delimiter, close: token.span }
+                )?;
+                if expected != delimiter {
+                    return Err(TokenTreeError::Mismatched {
+                        expected,
+                        found: delimiter,
+                        open,
+                        close: token.span,
+                    });
+                }
+                TokenTree::Group(Group {
+                    delimiter,
+                    open,
+                    close: token.span,
+                    children,
+                })
+            } else {
+                TokenTree::Leaf(token)
+            };
+
+            stack.last_mut().map(|(_, _, children)| children).unwrap_or(&mut top_level)
+                .push(tree);
+        }
+
+        match stack.pop() {
+            Some((delimiter, open, _)) => Err(TokenTreeError::Unclosed { delimiter, open }),
+            None => Ok(top_level),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_token_tree {
+    use super::*;
+    use crate::Lexer;
+
+    fn trees(source: &str) -> Result<Vec<TokenTree<'_>>, TokenTreeError> {
+        TokenTree::from_tokens(Lexer::new(source).spanned())
+    }
+
+    #[test]
+    fn group_tokens() {
+        let source = "#[cfg(any(a, b))] fn f() { [1, 2] }";
+        let result = trees(source).unwrap();
+        // `#`, `[...]`, `fn`, `f`, `()`, `{...}`
+        assert_eq!(result.len(), 6);
+        let attribute = match &result[1] {
+            TokenTree::Group(group) => group,
+            _ => panic!("Expected a group"),
+        };
+        assert_eq!(attribute.delimiter, Delimiter::Bracket);
+        assert_eq!(&source[attribute.inner_span().byte_range()], "cfg(any(a, b))");
+        assert_eq!(attribute.children.len(), 2);
+
+        match &result[4] {
+            TokenTree::Group(group) => {
+                assert_eq!(group.delimiter, Delimiter::Parenthesis);
+                assert!(group.children.is_empty());
+                assert_eq!(&source[group.open.byte_range()], "(");
+                assert_eq!(&source[group.close.byte_range()], ")");
+            }
+            _ => panic!("Expected a group"),
+        }
+        assert_eq!(&source[result[5].span().byte_range()], "{ [1, 2] }");
+
+        match trees("(a]") {
+            Err(TokenTreeError::Mismatched { expected, found, open, close }) => {
+                assert_eq!((expected, found), (Delimiter::Parenthesis, Delimiter::Bracket));
+                assert_eq!((open.start.byte_offset, close.start.byte_offset), (0, 2));
+            }
+            other => panic!("Unexpected {:?}", other),
+        }
+        assert!(matches!(
+            trees("fn f() { if x { }"),
+            Err(TokenTreeError::Unclosed { delimiter: Delimiter::Brace, open }) if open.start.byte_offset == 7
+        ));
+        assert!(matches!(
+            trees("a }"),
+            Err(TokenTreeError::UnexpectedClose { delimiter: Delimiter::Brace, .. })
+        ));
+    }
+}
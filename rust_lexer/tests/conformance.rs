@@ -0,0 +1,119 @@
+//! Compare our tokens with the ones of `proc_macro2` on the checked-in
+//! corpus and on our own sources. Run with
+//! `cargo test --features conformance --test conformance`.
+#![cfg(feature = "conformance")]
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use rust_lexer::{parse_with_proc_macro2, Lexer, LexerConfig, SpannedToken};
+
+/// How many tokens around the first mismatch are shown
+const CONTEXT_TOKENS: usize = 3;
+
+/// Our tokens, or our error as the stream ends at the first one
+fn lex(source: &str) -> Result<Vec<SpannedToken<'_>>, String> {
+    let mut lexer = Lexer::new(source).spanned();
+    // `proc_macro2` drops the plain comments
+    let tokens = lexer.by_ref()
+        .filter(|token| !token.token.is_trivia())
+        .collect();
+    match lexer.diagnostics().first() {
+        Some(error) => Err(format!("{:?}", error)),
+        None => Ok(tokens),
+    }
+}
+
+/// Where the tokens of both lexers differ, or the error of one of them
+fn compare(source: &str) -> Result<Option<usize>, String> {
+    let ours = lex(source).map_err(|error| format!("we failed: {}", error))?;
+    let theirs = parse_with_proc_macro2(source, 0, LexerConfig::default())
+        .map_err(|error| format!("proc_macro2 failed: {:?}", error))?;
+    Ok(first_mismatch(&ours, &theirs))
+}
+
+/// The index of the first token that differs, or where the shorter ends
+fn first_mismatch(ours: &[SpannedToken<'_>], theirs: &[SpannedToken<'_>]) -> Option<usize> {
+    ours.iter().zip(theirs).position(|(ours, theirs)| ours != theirs)
+        .or_else(|| (ours.len() != theirs.len()).then(|| ours.len().min(theirs.len())))
+}
+
+fn describe(tokens: &[SpannedToken<'_>], around: usize) -> String {
+    let mut result = String::new();
+    let start = around.saturating_sub(CONTEXT_TOKENS);
+    for (index, token) in tokens.iter().enumerate().skip(start).take(2 * CONTEXT_TOKENS + 1) {
+        let marker = if index == around { ">" } else { " " };
+        let (start, end) = (token.span.start, token.span.end);
+        let _ = writeln!(
+            result, "  {} {}:{}..{}:{} {:?}",
+            marker, start.line + 1, start.column + 1, end.line + 1, end.column + 1, token.token,
+        );
+    }
+    result
+}
+
+/// Describe the first difference, shrinking the source to the line where it
+/// is when that line alone still shows it
+fn report(path: &Path, source: &str, mismatch: usize) -> String {
+    // Defensive unwrap, `compare` already lexed this source
+    let ours = lex(source).unwrap();
+    let line = ours.get(mismatch).or(ours.last()).map(|token| token.span.start.line).unwrap_or(0);
+    let line_text = source.lines().nth(line).unwrap_or("");
+
+    let (source, mismatch) = match compare(line_text) {
+        Ok(Some(line_mismatch)) => (line_text, line_mismatch),
+        _ => (source, mismatch),
+    };
+    // Defensive unwraps, `compare` already lexed and parsed this source
+    let ours = lex(source).unwrap();
+    let theirs = parse_with_proc_macro2(source, 0, LexerConfig::default()).unwrap();
+
+    let mut result = format!("{}:{}: tokens differ\n", path.display(), line + 1);
+    if source.len() == line_text.len() {
+        let _ = writeln!(result, "minimized to the line: {}", line_text);
+    }
+    let _ = write!(result, "ours:\n{}theirs:\n{}", describe(&ours, mismatch), describe(&theirs, mismatch));
+    result
+}
+
+fn corpus() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files = Vec::new();
+    for directory in ["tests/corpus", "src", "benches"] {
+        for entry in std::fs::read_dir(root.join(directory)).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|extension| extension == "rs") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn same_tokens_as_proc_macro2() {
+    let mut failures = Vec::new();
+    for path in corpus() {
+        let source = std::fs::read_to_string(&path).unwrap();
+        match compare(&source) {
+            Ok(None) => {},
+            Ok(Some(mismatch)) => failures.push(report(&path, &source, mismatch)),
+            Err(error) => failures.push(format!("{}: {}", path.display(), error)),
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+/// The tokens of `proc_macro2` don't depend on ours, a lexer eating the `.`
+/// of `1.max(2)` must differ from them
+#[test]
+fn token_boundary_off_by_one() {
+    let source = "1.max(2)";
+    let theirs = parse_with_proc_macro2(source, 0, LexerConfig::default()).unwrap();
+    assert_eq!(first_mismatch(&lex(source).unwrap(), &theirs), None);
+
+    // `1.` alone is a float with the span the wrong token would have
+    let mut ours = lex("1.").unwrap();
+    ours.extend(lex(source).unwrap().into_iter().skip(2));
+    assert_eq!(first_mismatch(&ours, &theirs), Some(0));
+}
@@ -0,0 +1,20 @@
+//! Tokens whose end depends on what follows them, a lexer that stops one
+//! char too early or too late fails here
+
+fn boundaries(x: ((u8, u8), u8)) {
+    let _ = 1.max(2);
+    let _ = 1..2;
+    let _ = 1.5.max(2.5);
+    let _ = 1e3f32.abs();
+    let _ = 0x1F.min(2);
+    let _ = x.0.1;
+    let _ = 'a'.is_alphabetic();
+    let _ = "a".len();
+    let _ = b"a".len();
+    let _ = r#"a"#.len();
+    let _ = 1u8..=2u8;
+    let _ = &&x;
+    let _ = -1i8;
+    let _ = a<=b||c>>=d;
+    let _: &'static str = "a";
+}
@@ -0,0 +1,25 @@
+//! Inner doc of the file
+//! on two lines
+
+/// Outer doc
+/// with `code`
+pub struct Documented;
+
+/** Block doc */
+pub struct BlockDocumented;
+
+/*! Inner block doc */
+
+// plain comment // with slashes
+/* plain /* nested */ block */
+/*
+ * multi line
+ */
+pub fn f(/* inline */ x: u8) -> u8 {
+    x // trailing
+}
+
+/**/
+/***/
+//// not a doc comment
+/*** not a doc comment either */
@@ -0,0 +1,70 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet as Set};
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Shape<'a, T: Display + ?Sized = str> {
+    Circle { radius: f64 },
+    Named(&'a T),
+    Empty,
+}
+
+pub trait Area {
+    const SIDES: usize;
+    type Output;
+    fn area(&self) -> Self::Output;
+}
+
+impl<'a> Area for Shape<'a> {
+    const SIDES: usize = 0;
+    type Output = f64;
+    fn area(&self) -> f64 {
+        match *self {
+            Shape::Circle { radius } if radius >= 0.0 => 3.14 * radius * radius,
+            Shape::Named(..) | Shape::Empty => 0.0,
+            _ => -1.0,
+        }
+    }
+}
+
+unsafe impl<'a> Send for Shape<'a> {}
+
+pub async fn run<F>(f: F) -> Result<(), Box<dyn std::error::Error + 'static>>
+where
+    F: FnOnce(&mut Vec<u8>) -> bool,
+{
+    let mut map: HashMap<&'static str, Set<u8>> = HashMap::new();
+    map.entry("a").or_default().insert(1);
+    let mut buffer = vec![0u8; 4];
+    'outer: for i in 0..=10 {
+        if i % 2 == 0 && i != 4 || !f(&mut buffer) {
+            continue 'outer;
+        }
+        let r#type = i << 2 >> 1;
+        buffer[0] ^= r#type as u8;
+        buffer[1] |= 1;
+        buffer[2] &= !0;
+        let _closure = |x: u8| -> u8 { x.wrapping_add(1) };
+    }
+    let _ = buffer.iter().map(|&x| x as u32).sum::<u32>();
+    let _ = Some(1).ok_or(fmt::Error)?;
+    Ok(())
+}
+
+macro_rules! square {
+    ($x:expr) => { $x * $x };
+    ($($x:expr),+ $(,)?) => { ($(square!($x)),+) };
+}
+
+fn main() {
+    let _ = square!(2);
+    let _: () = ();
+    let _ = [(); 2];
+    let x = &&1;
+    let _ = *x as *const i32;
+    let _ = 1..2;
+    let _ = ..;
+    let _ = 1.0..;
+    let _ = std::mem::size_of::<Shape<'_>>();
+}
@@ -0,0 +1,29 @@
+//! Literals of every kind, with the suffixes and prefixes they can take
+
+const INTEGERS: [u64; 8] = [0, 1_000, 0xFF_u64 as u64, 0o777, 0b1010_1010, 1u8 as u64, 42usize as u64, 0x1F];
+const FLOATS: [f64; 6] = [1.5, 1.5e3, 2E-4, 3f64, 0.1_f64, 1e10];
+const TUPLE_FIELD: u8 = (1, 2).0;
+
+fn strings() {
+    let _ = "plain";
+    let _ = "escapes \n \t \\ \" \' \0 \x7F \u{1F600}";
+    let _ = "multi
+line";
+    let _ = "continuation \
+             of the line";
+    let _ = r"raw \n";
+    let _ = r#"raw with "quotes""#;
+    let _ = r##"raw with "# inside"##;
+    let _ = b"bytes \x00";
+    let _ = br"raw bytes";
+    let _ = br#"raw "bytes""#;
+    let _ = c"c string";
+    let _ = cr#"raw c string"#;
+    let _ = 'a';
+    let _ = '\'';
+    let _ = '\u{10FFFF}';
+    let _ = 'é';
+    let _ = b'a';
+    let _ = b'\\';
+    let _ = "é ü 日本";
+}
@@ -0,0 +1,258 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use rust_lexer::{collect_docs, Delimiter, Literal, LiteralValue, Peekable, Span, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::FromTokenStream;
+
+/// `#[meta]`, or `#![meta]` for the inner attributes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute<'a> {
+    pub is_inner: bool,
+    pub meta: Meta<'a>,
+    /// The tokens between the brackets
+    pub tokens: Vec<SpannedToken<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Meta<'a> {
+    /// `inline` or `rustfmt::skip`
+    Path(Vec<&'a str>),
+    /// `derive(Clone, Debug)`
+    List {
+        path: Vec<&'a str>,
+        items: Vec<Meta<'a>>,
+    },
+    /// `doc = "..."`, the value is kept as the tokens of the expression
+    NameValue {
+        path: Vec<&'a str>,
+        value: Vec<SpannedToken<'a>>,
+    },
+    /// Tokens that are not a meta, as the literal of `#[attribute(1)]`
+    Other(Vec<SpannedToken<'a>>),
+}
+
+impl<'a> FromTokenStream<'a> for Attribute<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let (is_inner, start) = match eat_symbol(token_stream, Symbol::Pound) {
+                Some(start) => (false, start),
+                None => (true, eat_symbol(token_stream, Symbol::Shebang)?),
+            };
+            // the brackets are matched with the other delimiters so that
+            // `#[cfg(any(a, b))]` and `#[doc = x[0]]` are not cut early
+            let (tokens, brackets) = eat_group(token_stream, Delimiter::Bracket)?;
+            Some(Attribute { is_inner, meta: Meta::from_tokens(tokens.clone()), tokens, span: start.join(&brackets) })
+        })
+    }
+}
+
+impl<'a> Attribute<'a> {
+    /// If the path of the attribute is `name`, as `"inline"` or `"rustfmt::skip"`
+    pub fn is(&self, name: &str) -> bool {
+        self.meta.is(name)
+    }
+
+    /// The items of a list as `derive(A, B)`, empty for the other attributes
+    pub fn list_items(&self) -> &[Meta<'a>] {
+        self.meta.list_items()
+    }
+
+    /// The value of `name = "value"` if the attribute is one with a string
+    pub fn name_value(&self, name: &str) -> Option<String> {
+        self.meta.name_value(name)
+    }
+
+    /// The last segment of the paths in `#[derive(...)]`, as `Serialize` for
+    /// `serde::Serialize`. Other attributes derive nothing.
+    pub fn derives(&self) -> Vec<&'a str> {
+        if !self.is("derive") {
+            return Vec::new();
+        }
+        self.list_items().iter()
+            .filter_map(|item| match item {
+                Meta::Path(path) => path.last().copied(),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl<'a> Meta<'a> {
+    pub(crate) fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Self {
+        let mut token_stream = sub_stream(tokens.clone());
+        let Some(path) = meta_path(&mut token_stream) else {
+            return Meta::Other(tokens);
+        };
+        if token_stream.peek(0).is_none() {
+            return Meta::Path(path);
+        }
+        if eat_symbol(&mut token_stream, Symbol::Eq).is_some() {
+            let value = token_stream.collect::<Vec<_>>();
+            return if value.is_empty() { Meta::Other(tokens) } else { Meta::NameValue { path, value } };
+        }
+        match eat_group(&mut token_stream, Delimiter::Parenthesis) {
+            Some((items, _)) if token_stream.peek(0).is_none() => Meta::List {
+                path,
+                items: split(items, Symbol::Comma).into_iter().map(Meta::from_tokens).collect(),
+            },
+            _ => Meta::Other(tokens),
+        }
+    }
+
+    /// The path of the meta, empty for [`Meta::Other`]
+    pub fn path(&self) -> &[&'a str] {
+        match self {
+            Meta::Path(path) | Meta::List { path, .. } | Meta::NameValue { path, .. } => path,
+            Meta::Other(_) => &[],
+        }
+    }
+
+    /// If the path is `name`, whose segments are separated by `::`
+    pub fn is(&self, name: &str) -> bool {
+        let path = self.path();
+        !path.is_empty() && path.len() == name.split("::").count() && path.iter().zip(name.split("::")).all(|(a, b)| *a == b)
+    }
+
+    /// The items of a list, empty for the other metas
+    pub fn list_items(&self) -> &[Meta<'a>] {
+        match self {
+            Meta::List { items, .. } => items,
+            _ => &[],
+        }
+    }
+
+    /// The value of `name = "value"` if the meta is one with a string
+    pub fn name_value(&self, name: &str) -> Option<String> {
+        match self {
+            Meta::NameValue { value, .. } if self.is(name) => match value.as_slice() {
+                [SpannedToken { token: Token::Literal(literal @ (Literal::String(_) | Literal::RawString(..))), .. }] => {
+                    match literal.value()? {
+                        Ok(LiteralValue::Str(text)) => Some(text.to_string()),
+                        _ => None,
+                    }
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Parse a path as `rustfmt::skip`, keywords are allowed as in `unsafe(no_mangle)`
+fn meta_path<'a>(token_stream: &mut SubStream<'a>) -> Option<Vec<&'a str>> {
+    eat_symbol(token_stream, Symbol::PathSep);
+    let mut path = Vec::new();
+    loop {
+        path.push(match *peek_token(token_stream, 0)? {
+            Token::Identifier(identifier) => identifier.name(),
+            Token::Keyword(keyword) => keyword.into(),
+            _ => return None,
+        });
+        token_stream.consume(1);
+        if eat_symbol(token_stream, Symbol::PathSep).is_none() {
+            return Some(path);
+        }
+    }
+}
+
+/// Parse the outer attributes at the start of the stream
+pub(crate) fn attributes<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Vec<Attribute<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    let mut attributes = Vec::new();
+    while !is_symbol(token_stream, 0, Symbol::Shebang) {
+        match Attribute::from_tokens_stream(token_stream) {
+            Some(attribute) => attributes.push(attribute),
+            None => break,
+        }
+    }
+    attributes
+}
+
+/// Parse the inner attributes at the start of the stream, as the ones at the
+/// start of a file or of the braces of a module
+pub(crate) fn inner_attributes<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Vec<Attribute<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    let mut attributes = Vec::new();
+    while is_symbol(token_stream, 0, Symbol::Shebang) {
+        match Attribute::from_tokens_stream(token_stream) {
+            Some(attribute) => attributes.push(attribute),
+            None => break,
+        }
+    }
+    attributes
+}
+
+/// The text of the `#[doc]` attributes, which are the doc comments once
+/// desugared, as rustdoc shows it
+pub fn documentation(attributes: &[Attribute<'_>]) -> String {
+    let tokens = attributes.iter()
+        .flat_map(|attribute| {
+            let pound = if attribute.is_inner { Symbol::Shebang } else { Symbol::Pound };
+            let spanned = |symbol| SpannedToken { token: Token::Symbol(symbol), span: attribute.span };
+            [spanned(pound), spanned(Symbol::OpenBraket)].into_iter()
+                .chain(attribute.tokens.iter().cloned())
+                .chain([spanned(Symbol::CloseBraket)])
+        })
+        .collect::<Vec<_>>();
+    collect_docs(&tokens).into_iter()
+        .map(|doc| doc.text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test_attribute {
+    use super::*;
+    use crate::Module;
+
+    #[test]
+    fn parse_metas() {
+        let source = "
+            #![allow(dead_code)]
+            //! The module
+            /// The function
+            #[inline]
+            #[rustfmt::skip]
+            #[derive(Clone, serde::Serialize)]
+            #[cfg_attr(feature = \"python\", module(graph))]
+            #[deprecated(since = \"1.0\", note = r\"use g\")]
+            #[custom(1 + 2)]
+            #[doc = concat!(\"a\", \"b\")]
+            fn f() {}
+        ";
+        let module = Module::parse(source).unwrap();
+        assert!(module.attributes.iter().all(|attribute| attribute.is_inner));
+        assert!(module.attributes[0].is("allow"));
+        assert_eq!(module.module_doc, "The module");
+
+        let attributes = &module.functions[0].attributes;
+        assert_eq!(documentation(attributes), "The function");
+        assert!(attributes[1].is("inline") && attributes[1].meta == Meta::Path(alloc::vec!["inline"]));
+        assert!(attributes[2].is("rustfmt::skip") && !attributes[2].is("skip"));
+        assert_eq!(attributes[3].derives(), ["Clone", "Serialize"]);
+
+        let items = attributes[4].list_items();
+        assert_eq!(items[0].name_value("feature").as_deref(), Some("python"));
+        assert!(items[1].is("module") && items[1].list_items()[0].is("graph"));
+
+        let items = attributes[5].list_items();
+        assert_eq!(items[0].name_value("since").as_deref(), Some("1.0"));
+        assert_eq!(items[1].name_value("note").as_deref(), Some("use g"));
+        assert!(items[1].name_value("since").is_none());
+
+        assert!(matches!(attributes[6].list_items(), [Meta::Other(tokens)] if tokens.len() == 3));
+        assert!(matches!(&attributes[7].meta, Meta::NameValue { value, .. } if value.len() == 7));
+        assert!(attributes[7].name_value("doc").is_none());
+        assert!(source[attributes[3].span.byte_range()].starts_with("#[derive") && !attributes[3].is_inner);
+    }
+}
@@ -0,0 +1,362 @@
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Symbol};
+use crate::stream::*;
+use crate::{
+    documentation, Attribute, Enum, Field, Fields, Function, Impl, Meta, Module, Struct, Trait, Union, Use,
+    Variant, AssociatedConst, AssociatedType,
+};
+
+/// The configuration options a crate is compiled with, as the names `unix`
+/// or `test` and the values `feature = "python"` or `target_os = "linux"`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CfgSet {
+    names: BTreeSet<String>,
+    values: BTreeSet<(String, String)>,
+}
+
+impl CfgSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The options rustc sets for a target triple as `x86_64-unknown-linux-gnu`:
+    /// `target_arch`, `target_os`, `target_family` with its name as `unix`,
+    /// `target_env` and `target_pointer_width`
+    pub fn for_target(target_triple: &str) -> Self {
+        let components = target_triple.split('-').collect::<Vec<_>>();
+        let arch = match components.first().copied().unwrap_or_default() {
+            "i386" | "i586" | "i686" => "x86",
+            "riscv64gc" | "riscv64imac" => "riscv64",
+            "powerpc64le" => "powerpc64",
+            arch if arch.starts_with("armv") || arch.starts_with("thumbv") => "arm",
+            arch => arch,
+        };
+        let has = |name: &str| components.iter().any(|component| component.starts_with(name));
+        // android triples also contain `linux`
+        let os = if has("android") {
+            "android"
+        } else if has("linux") {
+            "linux"
+        } else if has("darwin") || has("macos") {
+            "macos"
+        } else if has("ios") {
+            "ios"
+        } else if has("windows") {
+            "windows"
+        } else if has("freebsd") {
+            "freebsd"
+        } else if has("wasi") {
+            "wasi"
+        } else {
+            "none"
+        };
+        let family = match os {
+            "android" | "linux" | "macos" | "ios" | "freebsd" => Some("unix"),
+            "windows" => Some("windows"),
+            _ if arch.starts_with("wasm") => Some("wasm"),
+            _ => None,
+        };
+        let pointer_width = match arch {
+            "x86_64" | "aarch64" | "riscv64" | "powerpc64" | "mips64" | "s390x" | "wasm64" => "64",
+            _ => "32",
+        };
+
+        let mut cfg_set = Self::new()
+            .with_value("target_arch", arch)
+            .with_value("target_os", os)
+            .with_value("target_pointer_width", pointer_width);
+        if let Some(family) = family {
+            cfg_set = cfg_set.with_value("target_family", family);
+            if family != "wasm" {
+                cfg_set = cfg_set.with_name(family);
+            }
+        }
+        // `gnueabihf` is the `gnu` environment
+        let env = components.get(3).and_then(|env| ["gnu", "musl", "msvc"].into_iter().find(|name| env.starts_with(name)));
+        if let Some(env) = env {
+            cfg_set = cfg_set.with_value("target_env", env);
+        }
+        cfg_set
+    }
+
+    /// Set a name as `unix`, `test` or `debug_assertions`
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.names.insert(name.to_string());
+        self
+    }
+
+    /// Set a value as `target_os = "linux"`, a key can have several values
+    pub fn with_value(mut self, key: &str, value: &str) -> Self {
+        self.values.insert((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Enable `feature = "name"`
+    pub fn with_feature(self, name: &str) -> Self {
+        self.with_value("feature", name)
+    }
+
+    /// Enable `target_feature = "name"`, as `avx2`
+    pub fn with_target_feature(self, name: &str) -> Self {
+        self.with_value("target_feature", name)
+    }
+
+    /// Evaluate a predicate as `all(unix, not(feature = "python"))`. The
+    /// options that are not set and the malformed predicates are false.
+    pub fn matches(&self, predicate: &Meta<'_>) -> bool {
+        match predicate {
+            Meta::Path(path) => match path.as_slice() {
+                ["true"] => true,
+                ["false"] => false,
+                [name] => self.names.contains(*name),
+                _ => false,
+            },
+            Meta::NameValue { path, .. } => match path.as_slice() {
+                [key] => predicate.name_value(key)
+                    .is_some_and(|value| self.values.contains(&(key.to_string(), value))),
+                _ => false,
+            },
+            Meta::List { items, .. } if predicate.is("all") => items.iter().all(|item| self.matches(item)),
+            Meta::List { items, .. } if predicate.is("any") => items.iter().any(|item| self.matches(item)),
+            Meta::List { items, .. } if predicate.is("not") => match items.as_slice() {
+                [item] => !self.matches(item),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Expand the `#[cfg_attr]` whose predicate is true, drop the others, and
+    /// tell if the `#[cfg]` of the attributes are all true. The `#[cfg]` are
+    /// kept as the items that remain are the ones they enable.
+    pub fn configure(&self, attributes: &mut Vec<Attribute<'_>>) -> bool {
+        let mut index = 0;
+        while index < attributes.len() {
+            if attributes[index].is("cfg_attr") {
+                let attribute = attributes.remove(index);
+                let expanded = self.expand_cfg_attr(&attribute);
+                // the expanded attributes can be `cfg_attr` themselves
+                attributes.splice(index..index, expanded);
+                continue;
+            }
+            index += 1;
+        }
+        attributes.iter()
+            .filter(|attribute| attribute.is("cfg"))
+            .all(|attribute| match attribute.list_items() {
+                [predicate] => self.matches(predicate),
+                _ => false,
+            })
+    }
+
+    /// The attributes of `#[cfg_attr(predicate, a, b)]`, as `#[a]` and `#[b]`,
+    /// if the predicate is true
+    fn expand_cfg_attr<'a>(&self, attribute: &Attribute<'a>) -> Vec<Attribute<'a>> {
+        let mut token_stream = sub_stream(attribute.tokens.clone());
+        token_stream.consume(1);
+        let Some((tokens, _)) = eat_group(&mut token_stream, Delimiter::Parenthesis) else {
+            return Vec::new();
+        };
+        let mut parts = split(tokens, Symbol::Comma).into_iter();
+        let Some(predicate) = parts.next() else {
+            return Vec::new();
+        };
+        if !self.matches(&Meta::from_tokens(predicate)) {
+            return Vec::new();
+        }
+        parts
+            .map(|tokens| Attribute {
+                is_inner: attribute.is_inner,
+                meta: Meta::from_tokens(tokens.clone()),
+                tokens,
+                span: attribute.span,
+            })
+            .collect()
+    }
+}
+
+/// An item that `#[cfg]` can remove, with the items it contains
+trait Configure {
+    /// Configure the item and what it contains, false if it's removed
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool;
+}
+
+fn retain<T: Configure>(items: &mut Vec<T>, cfg_set: &CfgSet) {
+    items.retain_mut(|item| item.configure(cfg_set));
+}
+
+impl Configure for Field<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Fields<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        match self {
+            Fields::Named(fields) | Fields::Tuple(fields) => retain(fields, cfg_set),
+            Fields::Unit => {},
+        }
+        true
+    }
+}
+
+impl Configure for Struct<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        self.fields.configure(cfg_set);
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Union<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        retain(&mut self.fields, cfg_set);
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Variant<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        self.fields.configure(cfg_set);
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Enum<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        retain(&mut self.variants, cfg_set);
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Function<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for AssociatedConst<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for AssociatedType<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Impl<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        retain(&mut self.consts, cfg_set);
+        retain(&mut self.types, cfg_set);
+        retain(&mut self.methods, cfg_set);
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Trait<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        retain(&mut self.consts, cfg_set);
+        retain(&mut self.types, cfg_set);
+        retain(&mut self.methods, cfg_set);
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Use<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        cfg_set.configure(&mut self.attributes)
+    }
+}
+
+impl Configure for Module<'_> {
+    fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        let is_enabled = cfg_set.configure(&mut self.attributes);
+        // a `cfg_attr` can add documentation
+        self.module_doc = documentation(&self.attributes);
+        retain(&mut self.uses, cfg_set);
+        retain(&mut self.enums, cfg_set);
+        retain(&mut self.structs, cfg_set);
+        retain(&mut self.unions, cfg_set);
+        retain(&mut self.traits, cfg_set);
+        retain(&mut self.impls, cfg_set);
+        retain(&mut self.functions, cfg_set);
+        self.mods.retain(|_, module| module.configure(cfg_set));
+        // the first enabled one of the `mod`s with the same name is kept
+        for mut module in core::mem::take(&mut self.shadowed_mods) {
+            if !self.mods.contains_key(&module.name) && module.configure(cfg_set) {
+                self.mods.insert(module.name.clone(), module);
+            }
+        }
+        is_enabled
+    }
+}
+
+impl Module<'_> {
+    /// Remove the items, fields, variants and submodules that the `#[cfg]`
+    /// disable with the options, and expand the `#[cfg_attr]` of the others.
+    /// The module itself is kept even if an inner `#![cfg]` disables it, the
+    /// result tells if it's enabled.
+    pub fn configure(&mut self, cfg_set: &CfgSet) -> bool {
+        Configure::configure(self, cfg_set)
+    }
+}
+
+#[cfg(test)]
+mod test_cfg {
+    use super::*;
+
+    #[test]
+    fn filter_items() {
+        let source = "
+            #![cfg_attr(feature = \"python\", doc = \"With python\")]
+            #[cfg(all(unix, not(feature = \"python\")))]
+            fn without_python() {}
+            #[cfg(any(target_os = \"windows\", feature = \"python\"))]
+            #[cfg_attr(test, cfg_attr(debug_assertions, derive(Debug)), inline)]
+            fn with_python() {}
+            #[cfg(target_feature = \"avx2\")]
+            fn avx2() {}
+            pub struct Point {
+                #[cfg(target_arch = \"x86_64\")]
+                x: u8,
+                #[cfg(not(target_arch = \"x86_64\"))]
+                y: u8,
+            }
+            enum Kind { #[cfg(feature = \"python\")] Python, #[cfg(unknown)] Unknown, Rust }
+            impl Point { #[cfg(test)] fn test() {} fn keep() {} }
+            #[cfg(true)]
+            use a::b;
+        ";
+        let cfg_set = CfgSet::for_target("x86_64-unknown-linux-gnu")
+            .with_feature("python")
+            .with_name("test")
+            .with_name("debug_assertions");
+        let mut module = Module::parse(source).unwrap();
+        assert!(module.configure(&cfg_set));
+        assert_eq!(module.module_doc, "With python");
+
+        let functions = module.functions.iter().map(|function| function.name.name()).collect::<Vec<_>>();
+        assert_eq!(functions, ["with_python"]);
+        let attributes = &module.functions[0].attributes;
+        assert!(attributes[1].is("derive") && attributes[2].is("inline") && attributes.len() == 3);
+
+        assert_eq!(module.structs[0].fields.iter().map(|field| field.name.unwrap().name()).collect::<Vec<_>>(), ["x"]);
+        assert_eq!(module.enums[0].variants.iter().map(|variant| variant.name.name()).collect::<Vec<_>>(), ["Python", "Rust"]);
+        assert_eq!(module.impls[0].methods.len(), 2);
+        assert_eq!(module.uses.len(), 1);
+
+        let mut module = Module::parse(source).unwrap();
+        assert!(module.configure(&CfgSet::for_target("aarch64-linux-android").with_target_feature("avx2")));
+        assert!(module.module_doc.is_empty());
+        let functions = module.functions.iter().map(|function| function.name.name()).collect::<Vec<_>>();
+        assert_eq!(functions, ["without_python", "avx2"]);
+        assert_eq!(module.impls[0].methods.len(), 1);
+        assert!(Module::parse("#![cfg(windows)]").unwrap().configure(&CfgSet::for_target("i686-pc-windows-msvc")));
+        assert!(!Module::parse("#![cfg(windows)]").unwrap().configure(&cfg_set));
+    }
+}
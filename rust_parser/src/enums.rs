@@ -0,0 +1,157 @@
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Identifier, Keyword, Peekable, Span, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{attributes, documentation, Attribute, Fields, FromTokenStream, Generics, Visibility};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub name: Identifier<'a>,
+    pub fields: Fields<'a>,
+    /// The expression after `=`, as in `A = 1 << 2`
+    pub discriminant: Option<Vec<SpannedToken<'a>>>,
+    pub span: Span,
+}
+
+impl<'a> Variant<'a> {
+    /// The doc comments of the variant
+    pub fn docs(&self) -> alloc::string::String {
+        documentation(&self.attributes)
+    }
+
+    fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Option<Self> {
+        let span = span_of(&tokens)?;
+        let mut token_stream = sub_stream(tokens);
+        let attributes = attributes(&mut token_stream);
+        // variants can't have a visibility, but macros may write one
+        Visibility::from_tokens_stream(&mut token_stream)?;
+        let name = eat_identifier(&mut token_stream)?;
+        let (fields, _) = Fields::from_tokens_stream(&mut token_stream)?;
+        let discriminant = match eat_symbol(&mut token_stream, Symbol::Eq) {
+            Some(_) => Some(token_stream.by_ref().collect::<Vec<_>>()).filter(|tokens| !tokens.is_empty()),
+            None => None,
+        };
+        token_stream.peek(0).is_none().then_some(Variant { attributes, name, fields, discriminant, span })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enum<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    pub name: Identifier<'a>,
+    pub generics: Generics<'a>,
+    pub variants: Vec<Variant<'a>>,
+    pub span: Span,
+}
+
+impl<'a> Enum<'a> {
+    /// If no variant has fields, as the enums that are only a set of values
+    pub fn is_fieldless(&self) -> bool {
+        self.variants.iter().all(|variant| variant.fields.is_empty())
+    }
+}
+
+impl<'a> FromTokenStream<'a> for Enum<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            eat_keyword(token_stream, Keyword::Enum)?;
+            let name = eat_identifier(token_stream)?;
+            let mut generics = Generics::from_tokens_stream(token_stream)?;
+            generics.where_clause(token_stream)?;
+            let (variants, end) = eat_group(token_stream, Delimiter::Brace)?;
+            let variants = split_variants(variants).into_iter()
+                .map(Variant::from_tokens)
+                .collect::<Option<Vec<_>>>()?;
+            Some(Enum { attributes, visibility, name, generics, variants, span: start.join(&end) })
+        })
+    }
+}
+
+/// Split the variants at the commas. [`split`] can't be used as the
+/// discriminants are expressions where `<` is mostly a comparison.
+fn split_variants(tokens: Vec<SpannedToken<'_>>) -> Vec<Vec<SpannedToken<'_>>> {
+    let mut token_stream = sub_stream(tokens);
+    let mut variants = Vec::new();
+    while token_stream.peek(0).is_some() {
+        let mut variant = take_expression(&mut token_stream, |token| *token == Token::Symbol(Symbol::Comma));
+        if eat_symbol(&mut token_stream, Symbol::Comma).is_none() {
+            // stopped at an unmatched closing bracket, keep it in the variant
+            variant.extend(token_stream.by_ref());
+        }
+        variants.push(variant);
+    }
+    variants
+}
+
+#[cfg(test)]
+mod test_enums {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use crate::Module;
+
+    #[test]
+    fn parse_enums() {
+        let source = "
+            #[derive(Clone, Copy)]
+            #[repr(u8)]
+            pub enum Flags {
+                /// Nothing
+                Empty,
+                A = 1 << 2,
+                B = Self::A as u8 | 1,
+                C = size_of::<Vec<u8>, >() as u8,
+                D = 64 >> 1,
+                E = size_of::<Vec<Vec<u8>>>() as u8,
+            }
+            enum Shape<T: Into<Vec<u8>>> {
+                Unit,
+                Tuple(pub T, Vec<Vec<T>>),
+                #[allow(unused)]
+                Struct { x: T, y: T },
+                Empty(),
+            }
+        ";
+        let module = Module::parse(source).unwrap();
+        let [flags, shape] = module.enums.as_slice() else {
+            panic!("{:?}", module.enums);
+        };
+
+        assert!(flags.is_fieldless());
+        assert_eq!(flags.visibility, Visibility::Public);
+        assert_eq!(flags.variants[0].docs(), "Nothing");
+        let discriminants = flags.variants.iter()
+            .map(|variant| variant.discriminant.as_ref().map(|tokens| {
+                tokens.iter().map(|token| token.token.to_string()).collect::<Vec<_>>().join(" ")
+            }))
+            .collect::<Vec<_>>();
+        assert_eq!(discriminants, [
+            None,
+            Some(String::from("1 << 2")),
+            Some(String::from("Self :: A as u8 | 1")),
+            Some(String::from("size_of :: < Vec < u8 > , > () as u8")),
+            Some(String::from("64 >> 1")),
+            Some(String::from("size_of :: < Vec < Vec < u8 >> > () as u8")),
+        ]);
+        assert!(source[flags.span.byte_range()].starts_with("#[derive"));
+
+        assert!(!shape.is_fieldless());
+        assert_eq!(shape.generics.params.len(), 1);
+        let Fields::Tuple(fields) = &shape.variants[1].fields else { panic!("{:?}", shape.variants[1].fields) };
+        assert_eq!(fields[1].ty.to_string(), "Vec<Vec<T>>");
+        let shapes = shape.variants.iter()
+            .map(|variant| (variant.name.name(), variant.fields.len(), matches!(variant.fields, Fields::Named(_))))
+            .collect::<Vec<_>>();
+        assert_eq!(shapes, [("Unit", 0, false), ("Tuple", 2, false), ("Struct", 2, true), ("Empty", 0, false)]);
+        assert_eq!(shape.variants[2].attributes.len(), 1);
+        assert_eq!(shape.variants[3].fields, Fields::Tuple(Vec::new()));
+    }
+}
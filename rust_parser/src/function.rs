@@ -0,0 +1,250 @@
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Identifier, Keyword, Lifetime, Literal, Peekable, Span, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{attributes, Attribute, FromTokenStream, Generics, Type, Visibility};
+
+/// The keywords before `fn`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FunctionQualifiers<'a> {
+    pub is_const: bool,
+    pub is_async: bool,
+    pub is_unsafe: bool,
+    pub is_extern: bool,
+    /// The ABI string of `extern "C"`, `None` if it's not written
+    pub abi: Option<&'a str>,
+}
+
+impl<'a> FromTokenStream<'a> for FunctionQualifiers<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        let mut qualifiers = FunctionQualifiers::default();
+        loop {
+            match peek_token(token_stream, 0) {
+                Some(Token::Keyword(Keyword::Const)) => qualifiers.is_const = true,
+                Some(Token::Keyword(Keyword::Async)) => qualifiers.is_async = true,
+                Some(Token::Keyword(Keyword::Unsafe)) => qualifiers.is_unsafe = true,
+                Some(Token::Keyword(Keyword::Extern)) => {
+                    qualifiers.is_extern = true;
+                    if let Some(Token::Literal(Literal::String(abi) | Literal::RawString(abi, _))) = peek_token(token_stream, 1) {
+                        qualifiers.abi = Some(*abi);
+                        token_stream.consume(1);
+                    }
+                },
+                _ => return Some(qualifiers),
+            }
+            token_stream.consume(1);
+        }
+    }
+}
+
+/// The binding of an argument, as `x`, `mut x` or `(a, b)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern<'a>(pub Vec<SpannedToken<'a>>);
+
+impl<'a> Pattern<'a> {
+    /// The name bound by the pattern if it's a single identifier, possibly
+    /// with `mut` or `ref`
+    pub fn identifier(&self) -> Option<Identifier<'a>> {
+        let tokens = self.0.iter()
+            .filter(|token| !matches!(token.token, Token::Keyword(Keyword::Mut | Keyword::Ref)))
+            .collect::<Vec<_>>();
+        match tokens.as_slice() {
+            [SpannedToken { token: Token::Identifier(identifier), .. }] => Some(*identifier),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Argument<'a> {
+    /// The receiver of a method, as `self`, `&'a mut self` or `self: Box<Self>`
+    SelfValue {
+        is_reference: bool,
+        lifetime: Option<Lifetime<'a>>,
+        is_mutable: bool,
+        ty: Option<Type<'a>>,
+    },
+    Typed {
+        attributes: Vec<Attribute<'a>>,
+        pattern: Pattern<'a>,
+        ty: Type<'a>,
+    },
+}
+
+impl<'a> Argument<'a> {
+    fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Option<Self> {
+        let mut token_stream = sub_stream(tokens);
+        let attributes = attributes(&mut token_stream);
+
+        let receiver = attempt(&mut token_stream, |token_stream| {
+            let is_reference = eat_symbol(token_stream, Symbol::And).is_some();
+            let lifetime = if is_reference { eat_lifetime(token_stream) } else { None };
+            let is_mutable = eat_keyword(token_stream, Keyword::Mut).is_some();
+            eat_keyword(token_stream, Keyword::SelfLowercase)?;
+            let ty = match eat_symbol(token_stream, Symbol::Colon) {
+                Some(_) => Some(Type::from_tokens(token_stream.collect())?),
+                None => None,
+            };
+            token_stream.peek(0).is_none().then_some(Argument::SelfValue { is_reference, lifetime, is_mutable, ty })
+        });
+        if receiver.is_some() {
+            return receiver;
+        }
+
+        let pattern = take_until(&mut token_stream, |token| *token == Token::Symbol(Symbol::Colon));
+        eat_symbol(&mut token_stream, Symbol::Colon)?;
+        let ty = Type::from_tokens(token_stream.collect())?;
+        Some(Argument::Typed { attributes, pattern: Pattern(pattern), ty })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    pub qualifiers: FunctionQualifiers<'a>,
+    pub name: Identifier<'a>,
+    pub generics: Generics<'a>,
+    pub arguments: Vec<Argument<'a>>,
+    pub return_type: Option<Type<'a>>,
+    /// The span of the braces of the body, `None` for a declaration without
+    /// body as in traits and extern blocks
+    pub body: Option<Span>,
+    pub span: Span,
+}
+
+impl<'a> FromTokenStream<'a> for Function<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            let qualifiers = FunctionQualifiers::from_tokens_stream(token_stream)?;
+            eat_keyword(token_stream, Keyword::Fn)?;
+            let name = eat_identifier(token_stream)?;
+            let mut generics = Generics::from_tokens_stream(token_stream)?;
+
+            let (arguments, _) = eat_group(token_stream, Delimiter::Parenthesis)?;
+            let arguments = split(arguments, Symbol::Comma).into_iter()
+                .map(Argument::from_tokens)
+                .collect::<Option<Vec<_>>>()?;
+
+            let return_type = match eat_symbol(token_stream, Symbol::RArrow) {
+                Some(_) => Some(Type::from_tokens(take_until(token_stream, |token| matches!(
+                    token, Token::Symbol(Symbol::OpenBraces | Symbol::Semi) | Token::Keyword(Keyword::Where)
+                )))?),
+                None => None,
+            };
+            generics.where_clause(token_stream)?;
+
+            let (body, end) = match eat_symbol(token_stream, Symbol::Semi) {
+                Some(end) => (None, end),
+                None => {
+                    let (_, body) = eat_group(token_stream, Delimiter::Brace)?;
+                    (Some(body), body)
+                },
+            };
+
+            Some(Function {
+                attributes,
+                visibility,
+                qualifiers,
+                name,
+                generics,
+                arguments,
+                return_type,
+                body,
+                span: start.join(&end),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_function {
+    use super::*;
+    use alloc::string::ToString;
+    use crate::{Bound, GenericParam, Module, WherePredicate};
+
+    #[test]
+    fn parse_functions() {
+        let source = "
+            /// Doc
+            #[inline]
+            pub(crate) const unsafe extern \"C\" fn first<'a, 'b: 'a, T: Clone + ?Sized, const N: usize>(
+                &'a mut self, (x, y): (u8, u8), mut values: Vec<Vec<T>>,
+            ) -> Result<&'a T, ()> where T: for<'c> Fn(&'c u8) -> bool, 'b: 'a {
+                if x < y { loop {} }
+            }
+            async fn second() {}
+            fn third(self: Box<Self>);
+            fn fourth<T: Into<Vec<u8>>>() -> Vec<Vec<T>> where Vec<T>: From<Vec<Vec<u8>>> { 1 << 2 }
+        ";
+        let module = Module::parse(source).unwrap();
+        let [first, second, third, fourth] = module.functions.as_slice() else {
+            panic!("{:?}", module.functions);
+        };
+
+        assert_eq!(first.name.name(), "first");
+        assert_eq!(first.attributes.len(), 2);
+        assert_eq!(first.visibility, Visibility::PublicCrate);
+        assert_eq!(first.qualifiers, FunctionQualifiers {
+            is_const: true,
+            is_async: false,
+            is_unsafe: true,
+            is_extern: true,
+            abi: Some("C"),
+        });
+        let params = first.generics.params.iter().map(|param| match param {
+            GenericParam::Lifetime { name, bounds } => (name.name(), bounds.len()),
+            GenericParam::Type { name, bounds, .. } => (name.name(), bounds.len()),
+            GenericParam::Const { name, ty, .. } => (name.name(), usize::from(ty.is_primitive())),
+        }).collect::<Vec<_>>();
+        assert_eq!(params, [("a", 0), ("b", 1), ("T", 2), ("N", 1)]);
+        assert!(matches!(
+            &first.generics.params[2],
+            GenericParam::Type { bounds, .. } if matches!(bounds[1], Bound::Trait { is_maybe: true, .. })
+        ));
+        assert!(matches!(
+            first.arguments[0],
+            Argument::SelfValue { is_reference: true, lifetime: Some(_), is_mutable: true, ty: None }
+        ));
+        let Argument::Typed { pattern, ty, .. } = &first.arguments[2] else { panic!() };
+        assert_eq!(pattern.identifier().map(|name| name.name()), Some("values"));
+        assert_eq!(ty.to_string(), "Vec<Vec<T>>");
+        assert!(matches!(&first.arguments[1], Argument::Typed { pattern, .. } if pattern.identifier().is_none()));
+        assert_eq!(first.return_type.as_ref().unwrap().to_string(), "Result<&'a T, ()>");
+        assert!(matches!(
+            first.generics.where_clause.as_slice(),
+            [WherePredicate::Type { bounds, .. }, WherePredicate::Lifetime { .. }]
+                if matches!(&bounds[0], Bound::Trait { for_lifetimes, .. } if for_lifetimes.len() == 1)
+        ));
+        let body = first.body.unwrap();
+        assert!(source[body.byte_range()].starts_with("{\n                if x < y"));
+        assert!(source[first.span.byte_range()].starts_with("/// Doc"));
+
+        assert!(second.qualifiers.is_async && second.arguments.is_empty() && second.return_type.is_none());
+        assert!(third.body.is_none());
+        assert!(matches!(&third.arguments[0], Argument::SelfValue { ty: Some(_), .. }));
+
+        // `>>` closes two angle brackets in the generics
+        assert!(matches!(
+            &fourth.generics.params[0],
+            GenericParam::Type { bounds, .. } if bounds[0].to_string() == "Into<Vec<u8>>"
+        ));
+        assert_eq!(fourth.return_type.as_ref().unwrap().to_string(), "Vec<Vec<T>>");
+        assert!(matches!(
+            fourth.generics.where_clause.as_slice(),
+            [WherePredicate::Type { bounds, .. }] if bounds[0].to_string() == "From<Vec<Vec<u8>>>"
+        ));
+    }
+}
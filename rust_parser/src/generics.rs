@@ -0,0 +1,288 @@
+use alloc::vec::Vec;
+use core::fmt;
+use rust_lexer::{Delimiter, Identifier, Keyword, Lifetime, Peekable, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{parse_path, FromTokenStream, Type, TypePath};
+
+/// The generic parameters of an item and its where clause
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Generics<'a> {
+    pub params: Vec<GenericParam<'a>>,
+    pub where_clause: Vec<WherePredicate<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenericParam<'a> {
+    /// `'a: 'b + 'c`
+    Lifetime {
+        name: Lifetime<'a>,
+        bounds: Vec<Lifetime<'a>>,
+    },
+    /// `T: Bound = Default`
+    Type {
+        name: Identifier<'a>,
+        bounds: Vec<Bound<'a>>,
+        default: Option<Type<'a>>,
+    },
+    /// `const N: usize = 3`
+    Const {
+        name: Identifier<'a>,
+        ty: Type<'a>,
+        default: Option<Vec<SpannedToken<'a>>>,
+    },
+}
+
+/// A bound of a generic type, as `'a`, `?Sized` or `for<'b> Fn(&'b u8)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bound<'a> {
+    Lifetime(Lifetime<'a>),
+    Trait {
+        for_lifetimes: Vec<Lifetime<'a>>,
+        /// If it's `?Trait`
+        is_maybe: bool,
+        path: TypePath<'a>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WherePredicate<'a> {
+    /// `'a: 'b`
+    Lifetime {
+        lifetime: Lifetime<'a>,
+        bounds: Vec<Lifetime<'a>>,
+    },
+    /// `for<'a> T: Bound`
+    Type {
+        for_lifetimes: Vec<Lifetime<'a>>,
+        ty: Type<'a>,
+        bounds: Vec<Bound<'a>>,
+    },
+}
+
+/// Parse the generic parameters between `<` and `>`, an item without them
+/// has empty generics. The where clause is parsed by [`Generics::where_clause`]
+/// as it comes later in the item.
+impl<'a> FromTokenStream<'a> for Generics<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        if !is_symbol(token_stream, 0, Symbol::LtOrClosedAngular) {
+            return Some(Generics::default());
+        }
+        attempt(token_stream, |token_stream| {
+            let params = angle_brackets(token_stream)?;
+            let params = split(params, Symbol::Comma).into_iter()
+                .map(generic_param)
+                .collect::<Option<Vec<_>>>()?;
+            Some(Generics { params, where_clause: Vec::new() })
+        })
+    }
+}
+
+impl<'a> Generics<'a> {
+    /// Parse the `where` clause if there is one, it goes on up to a `{` or
+    /// a `;`
+    pub fn where_clause<I>(&mut self, token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<()>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        if eat_keyword(token_stream, Keyword::Where).is_none() {
+            return Some(());
+        }
+        let predicates = take_until(token_stream, |token| matches!(
+            token, Token::Symbol(Symbol::OpenBraces | Symbol::Semi | Symbol::Eq)
+        ));
+        for predicate in split(predicates, Symbol::Comma) {
+            self.where_clause.push(where_predicate(predicate)?);
+        }
+        Some(())
+    }
+}
+
+/// Consume `<`, the tokens up to the matching `>` and the `>`. The `>>` of
+/// `<Vec<u8>>` closes both, its first half is left in the tokens.
+pub(crate) fn angle_brackets<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Vec<SpannedToken<'a>>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    attempt(token_stream, |token_stream| {
+        eat_symbol(token_stream, Symbol::LtOrClosedAngular)?;
+        // the angle brackets opened inside these ones, the ones inside other
+        // brackets are skipped with them
+        let mut angles = 0_usize;
+        let mut brackets = 0_usize;
+        let mut tokens = Vec::new();
+        loop {
+            let token = token_stream.next()?;
+            match token.token {
+                Token::Symbol(symbol) if Delimiter::from_open(symbol).is_some() => brackets += 1,
+                Token::Symbol(symbol) if Delimiter::from_close(symbol).is_some() => brackets = brackets.checked_sub(1)?,
+                _ if brackets > 0 => {},
+                Token::Symbol(Symbol::LtOrClosedAngular) => angles += 1,
+                Token::Symbol(Symbol::Shl) => angles += 2,
+                Token::Symbol(Symbol::GtOrOpenAngular) if angles == 0 => return Some(tokens),
+                Token::Symbol(Symbol::GtOrOpenAngular) => angles -= 1,
+                Token::Symbol(Symbol::Shr) if angles == 1 => {
+                    let (first, _) = split_shift(token.span);
+                    tokens.push(SpannedToken { token: Token::Symbol(Symbol::GtOrOpenAngular), span: first });
+                    return Some(tokens);
+                },
+                Token::Symbol(Symbol::Shr) => angles = angles.checked_sub(2)?,
+                _ => {},
+            }
+            tokens.push(token);
+        }
+    })
+}
+
+/// Parse `for<'a, 'b>` if it's there
+pub(crate) fn for_lifetimes<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Vec<Lifetime<'a>>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    if eat_keyword(token_stream, Keyword::For).is_none() {
+        return Some(Vec::new());
+    }
+    let lifetimes = angle_brackets(token_stream)?;
+    split(lifetimes, Symbol::Comma).into_iter()
+        .map(|lifetime| match lifetime.as_slice() {
+            [SpannedToken { token: Token::Lifetime(lifetime), .. }] => Some(*lifetime),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse `'b + 'c`
+fn lifetime_bounds(tokens: Vec<SpannedToken<'_>>) -> Option<Vec<Lifetime<'_>>> {
+    split(tokens, Symbol::Plus).into_iter()
+        .map(|lifetime| match lifetime.as_slice() {
+            [SpannedToken { token: Token::Lifetime(lifetime), .. }] => Some(*lifetime),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse bounds separated by `+` as `Clone + 'a + ?Sized`
+pub(crate) fn bounds(tokens: Vec<SpannedToken<'_>>) -> Option<Vec<Bound<'_>>> {
+    let mut token_stream = sub_stream(split_shifts(tokens));
+    let bounds = parse_bounds(&mut token_stream)?;
+    token_stream.peek(0).is_none().then_some(bounds)
+}
+
+/// Parse the bounds at the start of the stream, a trailing `+` is allowed
+pub(crate) fn parse_bounds<'a>(token_stream: &mut SubStream<'a>) -> Option<Vec<Bound<'a>>> {
+    let mut bounds = alloc::vec![bound(token_stream)?];
+    while eat_symbol(token_stream, Symbol::Plus).is_some() {
+        match attempt(token_stream, bound) {
+            Some(bound) => bounds.push(bound),
+            None => break,
+        }
+    }
+    Some(bounds)
+}
+
+fn bound<'a>(token_stream: &mut SubStream<'a>) -> Option<Bound<'a>> {
+    if let Some(lifetime) = eat_lifetime(token_stream) {
+        return Some(Bound::Lifetime(lifetime));
+    }
+    // a bound can be wrapped in parentheses
+    if is_symbol(token_stream, 0, Symbol::OpenParenthesis) {
+        let (inner, _) = eat_group(token_stream, Delimiter::Parenthesis)?;
+        let mut inner = sub_stream(inner);
+        let bound = bound(&mut inner)?;
+        return inner.peek(0).is_none().then_some(bound);
+    }
+    let for_lifetimes = for_lifetimes(token_stream)?;
+    let is_maybe = eat_symbol(token_stream, Symbol::Question).is_some();
+    let path = parse_path(token_stream)?;
+    Some(Bound::Trait { for_lifetimes, is_maybe, path })
+}
+
+impl<'a> Bound<'a> {
+    /// Match the bound against a pattern as [`Type::matches`] does
+    pub fn matches(&self, pattern: &Bound<'_>) -> bool {
+        match (self, pattern) {
+            (Bound::Lifetime(lifetime), Bound::Lifetime(pattern)) => lifetime.name() == pattern.name(),
+            (Bound::Trait { is_maybe, path, .. }, Bound::Trait { is_maybe: pattern_maybe, path: pattern, .. }) => {
+                is_maybe == pattern_maybe && path.matches(pattern)
+            },
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Bound<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bound::Lifetime(lifetime) => write!(f, "{}", lifetime),
+            Bound::Trait { for_lifetimes, is_maybe, path } => {
+                if !for_lifetimes.is_empty() {
+                    f.write_str("for<")?;
+                    for (index, lifetime) in for_lifetimes.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str(", ")?;
+                        }
+                        write!(f, "{}", lifetime)?;
+                    }
+                    f.write_str("> ")?;
+                }
+                if *is_maybe {
+                    f.write_str("?")?;
+                }
+                write!(f, "{}", path)
+            },
+        }
+    }
+}
+
+fn generic_param(tokens: Vec<SpannedToken<'_>>) -> Option<GenericParam<'_>> {
+    let mut token_stream = sub_stream(tokens);
+    // attributes of generic parameters are rare, they are dropped
+    crate::attributes(&mut token_stream);
+
+    if let Some(name) = eat_lifetime(&mut token_stream) {
+        let bounds = if eat_symbol(&mut token_stream, Symbol::Colon).is_some() {
+            lifetime_bounds(token_stream.collect())?
+        } else {
+            Vec::new()
+        };
+        return Some(GenericParam::Lifetime { name, bounds });
+    }
+
+    if eat_keyword(&mut token_stream, Keyword::Const).is_some() {
+        let name = eat_identifier(&mut token_stream)?;
+        eat_symbol(&mut token_stream, Symbol::Colon)?;
+        let ty = Type::from_tokens(take_until(&mut token_stream, |token| *token == Token::Symbol(Symbol::Eq)))?;
+        let default = eat_symbol(&mut token_stream, Symbol::Eq).map(|_| token_stream.collect());
+        return Some(GenericParam::Const { name, ty, default });
+    }
+
+    let name = eat_identifier(&mut token_stream)?;
+    let bounds = if eat_symbol(&mut token_stream, Symbol::Colon).is_some() {
+        bounds(take_until(&mut token_stream, |token| *token == Token::Symbol(Symbol::Eq)))?
+    } else {
+        Vec::new()
+    };
+    let default = match eat_symbol(&mut token_stream, Symbol::Eq) {
+        Some(_) => Some(Type::from_tokens(token_stream.by_ref().collect())?),
+        None => None,
+    };
+    token_stream.peek(0).is_none().then_some(GenericParam::Type { name, bounds, default })
+}
+
+fn where_predicate(tokens: Vec<SpannedToken<'_>>) -> Option<WherePredicate<'_>> {
+    let mut token_stream = sub_stream(tokens);
+    if let Some(lifetime) = eat_lifetime(&mut token_stream) {
+        eat_symbol(&mut token_stream, Symbol::Colon)?;
+        let bounds = lifetime_bounds(token_stream.collect())?;
+        return Some(WherePredicate::Lifetime { lifetime, bounds });
+    }
+    let for_lifetimes = for_lifetimes(&mut token_stream)?;
+    let ty = Type::from_tokens(take_until(&mut token_stream, |token| *token == Token::Symbol(Symbol::Colon)))?;
+    eat_symbol(&mut token_stream, Symbol::Colon)?;
+    let bounds = bounds(token_stream.collect())?;
+    Some(WherePredicate::Type { for_lifetimes, ty, bounds })
+}
@@ -0,0 +1,260 @@
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Identifier, Keyword, Peekable, Span, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{attributes, bounds, inner_attributes, Attribute, Bound, FromTokenStream, Function, Generics, Type, Visibility};
+
+/// `const NAME: Type = value;` in an impl or a trait
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssociatedConst<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    pub name: Identifier<'a>,
+    pub ty: Type<'a>,
+    /// `None` in traits without a default value
+    pub value: Option<Vec<SpannedToken<'a>>>,
+    pub span: Span,
+}
+
+impl<'a> FromTokenStream<'a> for AssociatedConst<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            eat_keyword(token_stream, Keyword::Const)?;
+            let name = eat_identifier(token_stream)?;
+            eat_symbol(token_stream, Symbol::Colon)?;
+            let ty = Type::from_tokens(take_until(token_stream, |token| matches!(
+                token, Token::Symbol(Symbol::Eq | Symbol::Semi)
+            )))?;
+            let value = eat_symbol(token_stream, Symbol::Eq)
+                .map(|_| take_expression(token_stream, |token| *token == Token::Symbol(Symbol::Semi)));
+            let end = eat_symbol(token_stream, Symbol::Semi)?;
+            Some(AssociatedConst { attributes, visibility, name, ty, value, span: start.join(&end) })
+        })
+    }
+}
+
+/// `type Name<'a>: Bound where Self: 'a = Type;` in an impl or a trait
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssociatedType<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    pub name: Identifier<'a>,
+    pub generics: Generics<'a>,
+    /// The bounds a trait puts on the type
+    pub bounds: Vec<Bound<'a>>,
+    /// `None` in traits without a default type
+    pub ty: Option<Type<'a>>,
+    pub span: Span,
+}
+
+impl<'a> FromTokenStream<'a> for AssociatedType<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            eat_keyword(token_stream, Keyword::Type)?;
+            let name = eat_identifier(token_stream)?;
+            let mut generics = Generics::from_tokens_stream(token_stream)?;
+            let bounds = match eat_symbol(token_stream, Symbol::Colon) {
+                Some(_) => bounds(take_until(token_stream, |token| matches!(
+                    token, Token::Symbol(Symbol::Eq | Symbol::Semi) | Token::Keyword(Keyword::Where)
+                )))?,
+                None => Vec::new(),
+            };
+            generics.where_clause(token_stream)?;
+            let ty = match eat_symbol(token_stream, Symbol::Eq) {
+                Some(_) => Some(Type::from_tokens(take_until(token_stream, |token| matches!(
+                    token, Token::Symbol(Symbol::Semi) | Token::Keyword(Keyword::Where)
+                )))?),
+                None => None,
+            };
+            // the where clause can also follow the type
+            generics.where_clause(token_stream)?;
+            let end = eat_symbol(token_stream, Symbol::Semi)?;
+            Some(AssociatedType { attributes, visibility, name, generics, bounds, ty, span: start.join(&end) })
+        })
+    }
+}
+
+/// The items in the braces of an impl or a trait
+#[derive(Default)]
+pub(crate) struct AssociatedItems<'a> {
+    pub(crate) consts: Vec<AssociatedConst<'a>>,
+    pub(crate) types: Vec<AssociatedType<'a>>,
+    pub(crate) methods: Vec<Function<'a>>,
+}
+
+impl<'a> AssociatedItems<'a> {
+    pub(crate) fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Option<Self> {
+        let mut token_stream = sub_stream(tokens);
+        let mut items = AssociatedItems::default();
+        // the inner attributes of the block are dropped
+        inner_attributes(&mut token_stream);
+        while token_stream.peek(0).is_some() {
+            if let Some(method) = Function::from_tokens_stream(&mut token_stream) {
+                items.methods.push(method);
+            } else if let Some(constant) = AssociatedConst::from_tokens_stream(&mut token_stream) {
+                items.consts.push(constant);
+            } else if let Some(ty) = AssociatedType::from_tokens_stream(&mut token_stream) {
+                items.types.push(ty);
+            } else {
+                // the macro calls are skipped, anything else fails the item
+                macro_call(&mut token_stream)?;
+            }
+        }
+        Some(items)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impl<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub is_unsafe: bool,
+    pub generics: Generics<'a>,
+    /// If it's `impl !Trait for Type`
+    pub is_negative: bool,
+    /// The trait implemented, `None` for an inherent impl
+    pub impl_trait: Option<Type<'a>>,
+    /// The type the impl is for
+    pub struct_name: Type<'a>,
+    pub consts: Vec<AssociatedConst<'a>>,
+    pub types: Vec<AssociatedType<'a>>,
+    pub methods: Vec<Function<'a>>,
+    pub span: Span,
+}
+
+impl<'a> FromTokenStream<'a> for Impl<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let is_unsafe = eat_keyword(token_stream, Keyword::Unsafe).is_some();
+            eat_keyword(token_stream, Keyword::Impl)?;
+            let mut generics = Generics::from_tokens_stream(token_stream)?;
+            let is_negative = eat_symbol(token_stream, Symbol::Not).is_some();
+
+            let header_end = |token: &Token<'a>| matches!(
+                token, Token::Symbol(Symbol::OpenBraces) | Token::Keyword(Keyword::Where)
+            );
+            let first = take_until(token_stream, |token| header_end(token) || *token == Token::Keyword(Keyword::For));
+            let (impl_trait, struct_name) = match eat_keyword(token_stream, Keyword::For) {
+                Some(_) => (Some(Type::from_tokens(first)?), Type::from_tokens(take_until(token_stream, header_end))?),
+                None => (None, Type::from_tokens(first)?),
+            };
+            if is_negative && impl_trait.is_none() {
+                return None;
+            }
+            generics.where_clause(token_stream)?;
+
+            let (items, end) = eat_group(token_stream, Delimiter::Brace)?;
+            let AssociatedItems { consts, types, methods } = AssociatedItems::from_tokens(items)?;
+            Some(Impl {
+                attributes,
+                is_unsafe,
+                generics,
+                is_negative,
+                impl_trait,
+                struct_name,
+                consts,
+                types,
+                methods,
+                span: start.join(&end),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_impls {
+    use super::*;
+    use alloc::string::ToString;
+    use crate::{Module, Trait, WherePredicate};
+
+    #[test]
+    fn parse_impls_and_traits() {
+        let source = "
+            impl<T: Clone> Wrapper<T> {
+                pub const LIMIT: bool = 1 < 2;
+                pub fn new(value: T) -> Self { Wrapper(value) }
+                my_macro!();
+                fn get(&self) -> &T { &self.0 }
+            }
+            unsafe impl<'a, T> Iterator for Wrapper<&'a T> where for<'b> &'b T: Clone {
+                type Item = Vec<&'a T>;
+                fn next(&mut self) -> Option<Self::Item> { None }
+            }
+            impl !Send for Wrapper<()> {}
+
+            pub unsafe trait Shape<'a>: Clone + 'a where Self: Sized {
+                #![allow(unused)]
+                const SIDES: usize = 0;
+                type Points<'b>: Iterator<Item = u8> where Self: 'b;
+                /// The area
+                fn area(&self) -> f64;
+                /// The sizes
+                getters! { width, height }
+                fn is_empty(&self) -> bool { self.area() == 0.0 }
+            }
+        ";
+        let module = Module::parse(source).unwrap();
+        let [inherent, iterator, negative] = module.impls.as_slice() else {
+            panic!("{:?}", module.impls);
+        };
+
+        assert!(inherent.impl_trait.is_none());
+        assert_eq!(inherent.struct_name.to_string(), "Wrapper<T>");
+        assert_eq!(inherent.methods.iter().map(|method| method.name.name()).collect::<Vec<_>>(), ["new", "get"]);
+        assert_eq!(inherent.consts[0].value.as_ref().map(Vec::len), Some(3));
+        assert_eq!(inherent.consts[0].visibility, Visibility::Public);
+
+        assert!(iterator.is_unsafe && !iterator.is_negative);
+        assert_eq!(iterator.impl_trait.as_ref().map(Type::to_string).as_deref(), Some("Iterator"));
+        assert_eq!(iterator.struct_name.to_string(), "Wrapper<&'a T>");
+        assert!(matches!(
+            iterator.generics.where_clause.as_slice(),
+            [WherePredicate::Type { for_lifetimes, .. }] if for_lifetimes.len() == 1
+        ));
+        assert_eq!(iterator.types[0].ty.as_ref().map(Type::to_string).as_deref(), Some("Vec<&'a T>"));
+
+        assert!(negative.is_negative);
+        assert_eq!(negative.impl_trait.as_ref().map(Type::to_string).as_deref(), Some("Send"));
+        assert!(negative.methods.is_empty());
+
+        let [shape] = module.traits.as_slice() else { panic!("{:?}", module.traits) };
+        let Trait { is_unsafe, visibility, supertraits, generics, consts, types, methods, .. } = shape;
+        assert!(is_unsafe);
+        assert_eq!(*visibility, Visibility::Public);
+        assert!(matches!(supertraits.as_slice(), [Bound::Trait { .. }, Bound::Lifetime(_)]));
+        assert_eq!((generics.params.len(), generics.where_clause.len()), (1, 1));
+        assert!(consts[0].value.is_some());
+        assert!(types[0].ty.is_none() && types[0].bounds.len() == 1 && types[0].generics.where_clause.len() == 1);
+        let defaults = methods.iter().map(|method| (method.name.name(), method.body.is_some())).collect::<Vec<_>>();
+        assert_eq!(defaults, [("area", false), ("is_empty", true)]);
+        assert_eq!(methods[0].attributes.len(), 1);
+        assert!(source[shape.span.byte_range()].ends_with("}"));
+
+        // a method that can't be parsed fails the impl instead of vanishing
+        let tokens = rust_lexer::Lexer::new("impl X { fn f(x: u8, ...) {} }").spanned()
+            .filter(|token| !token.token.is_trivia())
+            .collect();
+        assert!(Impl::from_tokens_stream(&mut sub_stream(tokens)).is_none());
+    }
+}
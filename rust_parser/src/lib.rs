@@ -1,16 +1,48 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+use alloc::boxed::Box;
 use alloc::vec::Vec;
 use alloc::string::String;
 use alloc::collections::BTreeMap;
-use rust_lexer::{Identifier, Lexer, Symbol, Keyword, Peekable, LexerError, Token};
+use rust_lexer::{Delimiter, DesugarDocs, Keyword, Lexer, Peekable, LexerError, Span, SpannedToken, Symbol, Token};
 
+mod stream;
+use stream::*;
 mod visibility;
+pub use visibility::*;
+mod attribute;
+pub use attribute::*;
+mod types;
+pub use types::*;
+mod generics;
+pub use generics::*;
+mod function;
+pub use function::*;
+mod structs;
+pub use structs::*;
+mod enums;
+pub use enums::*;
+mod impls;
+pub use impls::*;
+mod traits;
+pub use traits::*;
+mod uses;
+pub use uses::*;
+mod cfg;
+pub use cfg::*;
+#[cfg(feature = "std")]
+mod loader;
+#[cfg(feature = "std")]
+pub use loader::*;
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParserError<'a> {
     LexerError(LexerError<'a>),
-    UnexpectedToken(Token<'a>),
+    /// The first token of an item that could not be parsed
+    UnexpectedToken(Box<SpannedToken<'a>>),
 }
 
 impl<'a> From<LexerError<'a>> for ParserError<'a> {
@@ -19,13 +51,15 @@ impl<'a> From<LexerError<'a>> for ParserError<'a> {
     }
 }
 
-pub trait FromTokenStream: Sized {
-    fn from_tokens_stream<'a, I, const BUFFER_SIZE: usize>(
-        token_stream: &mut Peekable<Token<'a>, I, BUFFER_SIZE>
+/// Parse `Self` at the start of the stream, if it's not there `None` is
+/// returned and the stream is left as it was
+pub trait FromTokenStream<'a>: Sized {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
     ) 
     -> Option<Self>
     where
-        I: Iterator<Item=Token<'a>>;
+        I: Iterator<Item=SpannedToken<'a>>;
 }
 
 #[derive(Debug, Default)]
@@ -33,135 +67,172 @@ pub struct Module<'a> {
     pub file_path: String,
     pub module_doc: String,
     pub name: String,
+    /// The attributes of the `mod` item followed by the inner attributes, as
+    /// `#![allow(dead_code)]`
+    pub attributes: Vec<Attribute<'a>>,
+    /// If the module is `mod name { ... }` rather than in its own file
+    pub is_inline: bool,
+    /// The visibility of the `mod` item declaring the module
+    pub visibility: Visibility<'a>,
     pub uses: Vec<Use<'a>>,
-    //pub enums: Vec<Enum>,
-    //pub structs: Vec<Struct>,
+    pub enums: Vec<Enum<'a>>,
+    pub structs: Vec<Struct<'a>>,
+    pub unions: Vec<Union<'a>>,
     //pub types: Vec<TypeDefinition>,
-    //pub traits: Vec<TraitDefinition>,
+    pub traits: Vec<Trait<'a>>,
     //pub consts: Vec<Const>,
     //pub statics: Vec<Static>,
-    //pub impls: Vec<Impl>,
+    pub impls: Vec<Impl<'a>>,
     //pub macros: Vec<Macro>,
     //pub macro_calls: Vec<MacroCall>,
-    //pub functions: Vec<Function>,
+    pub functions: Vec<Function<'a>>,
     //pub externs: Vec<Extern>,
     pub mods: BTreeMap<String, Module<'a>>,
+    /// The `mod`s declared again with the name of one in `mods`, as
+    /// `#[cfg(windows)] mod imp;` after `#[cfg(unix)] mod imp;`, until
+    /// [`Module::configure`] keeps the first enabled one
+    pub shadowed_mods: Vec<Module<'a>>,
 }
 
-impl<'b> Module<'b> {
-    pub fn from_tokens_stream<'a, I, const BUFFER_SIZE: usize>(
-        token_stream: &mut Peekable<Token<'a>, I, BUFFER_SIZE>
+impl<'a> Module<'a> {
+    /// Lex and parse the items of a source file
+    pub fn parse(source: &'a str) -> Result<Self, ParserError<'a>> {
+        let mut tokens = Lexer::new(source).spanned();
+        // the parser sees the doc comments as `#[doc]` attributes
+        let items = DesugarDocs::new(tokens.by_ref().filter(|token| !token.token.is_trivia()));
+        let items = items.collect::<Vec<_>>();
+        if let Some(error) = tokens.diagnostics().first() {
+            return Err(error.clone().into());
+        }
+        Self::from_tokens_stream(&mut Peekable::new(items.into_iter()))
+    }
+
+    /// Parse the items up to the end of the stream, which must not contain
+    /// trivia
+    pub fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
     ) -> Result<Self, ParserError<'a>>
     where
-        I: Iterator<Item=Token<'a>>,
+        I: Iterator<Item=SpannedToken<'a>>,
     {
         let mut module = Module::default();
+        module.attributes = inner_attributes(token_stream);
+        module.module_doc = documentation(&module.attributes);
 
         while let Some(token) = token_stream.get(0) {
-            if let Some(uze) = <Use<'a>>::from_token_stream(&mut token_stream) {
+            if let Some(function) = Function::from_tokens_stream(token_stream) {
+                module.functions.push(function);
+                continue;
+            }
+            if let Some(structure) = Struct::from_tokens_stream(token_stream) {
+                module.structs.push(structure);
+                continue;
+            }
+            if let Some(enumeration) = Enum::from_tokens_stream(token_stream) {
+                module.enums.push(enumeration);
+                continue;
+            }
+            if let Some(union) = Union::from_tokens_stream(token_stream) {
+                module.unions.push(union);
+                continue;
+            }
+            if let Some(imp) = Impl::from_tokens_stream(token_stream) {
+                module.impls.push(imp);
+                continue;
+            }
+            if let Some(definition) = Trait::from_tokens_stream(token_stream) {
+                module.traits.push(definition);
+                continue;
+            }
+            if let Some(uze) = Use::from_tokens_stream(token_stream) {
                 module.uses.push(uze);
+                continue;
+            }
+            if let Some(declaration) = mod_item(token_stream) {
+                let child = declaration?;
+                if module.mods.contains_key(&child.name) {
+                    module.shadowed_mods.push(child);
+                } else {
+                    module.mods.insert(child.name.clone(), child);
+                }
+                continue;
+            }
+            // the items that are not modelled are skipped, anything else
+            // is an error rather than dropped
+            if unmodelled_item(token_stream).is_none() {
+                return Err(ParserError::UnexpectedToken(Box::new(token)));
             }
-            
-            return Err(ParserError::UnexpectedToken(token));
         }
 
         Ok(module)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Attribute<'a>(Vec<Token<'a>>);
-
-impl<'b> FromTokenStream for Attribute<'b> {
-    fn from_tokens_stream<'a, I, const BUFFER_SIZE: usize>(
-        token_stream: &mut Peekable<Token<'a>, I, BUFFER_SIZE>
-    ) -> Option<Self>
-    where
-        I: Iterator<Item=Token<'a>>,
-    {
-        if token_stream.get(0) != Some(Token::Symbol(Symbol::Pound)) 
-            || token_stream.get(1) != Some(Token::Symbol(Symbol::OpenBraket)) 
-            {
-            return None;
-        } 
-
-        let mut tokens = Vec::new();
-        while let Some(token) = token_stream.next() {
-            if token == Token::Symbol(Symbol::CloseBraket) {
-                return Some(Attribute(tokens));
-            }
-            tokens.push(token);
-        }
-
-        // mising closing braket
-        None
+/// Consume an item that the parser doesn't model, as a const, a static, a
+/// type alias, an extern crate or block or a macro call, returning its span.
+/// `None` if the next item is none of them.
+fn unmodelled_item<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Span>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    if let Some(span) = macro_call(token_stream) {
+        return Some(span);
     }
-}
-
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct Use<'a> {
-    pub visibility: Visibility,
-    pub attributes: Vec<Attribute<'a>>,
-    pub content: Vec<Identifier<'a>>,
-}
-
-impl<'b> FromTokenStream for Use<'b> {
-    fn from_tokens_stream<'a, I, const BUFFER_SIZE: usize>(
-        token_stream: &mut Peekable<Token<'a>, I, BUFFER_SIZE>
-    ) -> Option<Self>
-    where
-        I: Iterator<Item=Token<'a>>,
-    {
-        let mut uze = Use::default();
-
-        uze.visibility = Visibility::from_tokens_stream(token_stream)?;
-
-        let next_token = token_stream.get(0)?;
-        if next_token != Token::Keyword(Keyword::Use) {
+    attempt(token_stream, |token_stream| {
+        let start = token_stream.peek(0)?.span;
+        attributes(token_stream);
+        Visibility::from_tokens_stream(token_stream)?;
+        // `unsafe extern "C" { ... }`
+        eat_keyword(token_stream, Keyword::Unsafe);
+        let is_unmodelled = match peek_token(token_stream, 0)? {
+            // not `const fn`
+            Token::Keyword(Keyword::Const) => matches!(
+                peek_token(token_stream, 1),
+                Some(Token::Identifier(_) | Token::Symbol(Symbol::Underscore))
+            ),
+            Token::Keyword(Keyword::Static) => true,
+            Token::Keyword(Keyword::Type) => matches!(peek_token(token_stream, 1), Some(Token::Identifier(_))),
+            // not `extern "C" fn`
+            Token::Keyword(Keyword::Extern) => match peek_token(token_stream, 1) {
+                Some(Token::Keyword(Keyword::Crate) | Token::Symbol(Symbol::OpenBraces)) => true,
+                Some(Token::Literal(_)) => is_symbol(token_stream, 2, Symbol::OpenBraces),
+                _ => false,
+            },
+            _ => false,
+        };
+        if !is_unmodelled {
             return None;
         }
-
-        todo!();
-
-        Some(uze)
-    }
+        Some(start.join(&skip_item(token_stream)?))
+    })
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Visibility{
-    Private,
-    Public,
-    PublicCrate, 
-    PublicSuper,
-}
-
-impl Default for Visibility {
-    fn default() -> Self {
-        Visibility::Private
-    }
-}
-
-impl FromTokenStream for Visibility {
-    fn from_tokens_stream<'a, I, const BUFFER_SIZE: usize>(
-        token_stream: &mut Peekable<Token<'a>, I, BUFFER_SIZE>
-    ) -> Option<Self>
-    where
-        I: Iterator<Item=Token<'a>>,
-    {
-        Some(match (token_stream.get(0)?, token_stream.get(1)?, token_stream.get(2)?, token_stream.get(3)?) {
-            (Token::Keyword(Keyword::Pub), Token::Symbol(Symbol::OpenBraces), Token::Keyword(Keyword::Crate), Token::Symbol(Symbol::CloseBraces)) => {
-                token_stream.consume(4);
-                Visibility::PublicCrate
-            },
-            (Token::Keyword(Keyword::Pub), Token::Symbol(Symbol::OpenBraces), Token::Keyword(Keyword::Super), Token::Symbol(Symbol::CloseBraces)) => {
-                token_stream.consume(4);
-                Visibility::PublicSuper
-            },
-            (Token::Keyword(Keyword::Pub), _, _, _) => {
-                token_stream.consume(1);
-                Visibility::Public
-            },    
-            _ => Visibility::Private,
-        })
-    }
+/// Parse `mod name;`, which only declares the module, or `mod name { ... }`
+fn mod_item<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Result<Module<'a>, ParserError<'a>>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    let (attributes, visibility, name, body) = attempt(token_stream, |token_stream| {
+        let attributes = attributes(token_stream);
+        let visibility = Visibility::from_tokens_stream(token_stream)?;
+        eat_keyword(token_stream, Keyword::Mod)?;
+        let name = eat_identifier(token_stream)?;
+        let body = match eat_symbol(token_stream, Symbol::Semi) {
+            Some(_) => None,
+            None => Some(eat_group(token_stream, Delimiter::Brace)?.0),
+        };
+        Some((attributes, visibility, name, body))
+    })?;
+    let mut module = match body {
+        Some(tokens) => match Module::from_tokens_stream(&mut sub_stream(tokens)) {
+            Ok(module) => Module { is_inline: true, ..module },
+            Err(error) => return Some(Err(error)),
+        },
+        None => Module::default(),
+    };
+    module.attributes.splice(0..0, attributes);
+    module.module_doc = documentation(&module.attributes);
+    module.name = name.name().into();
+    module.visibility = visibility;
+    Some(Ok(module))
 }
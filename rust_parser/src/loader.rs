@@ -0,0 +1,316 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use std::path::{Path, PathBuf};
+use rust_lexer::{LexerError, Span};
+use crate::{documentation, Module, ParserError};
+
+/// A problem found while loading the files of a crate, the modules that
+/// could not be loaded are left empty in the tree
+#[derive(Debug)]
+pub enum LoadDiagnostic {
+    /// No file for `mod name;`, with the paths that were tried
+    MissingFile {
+        module: Vec<String>,
+        declared_in: PathBuf,
+        candidates: Vec<PathBuf>,
+    },
+    /// A `#[path]` naming the file of the module or of one of its parents
+    RecursiveModule {
+        module: Vec<String>,
+        path: PathBuf,
+    },
+    /// A file that could not be read
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// A file that could not be lexed or parsed, at the error
+    Unparsable {
+        path: PathBuf,
+        span: Span,
+    },
+}
+
+/// The path of a module from the crate root, with the rank of each module
+/// among the `mod`s declared with its name: 0 for the one in
+/// [`Module::mods`], then 1, 2... for the [`Module::shadowed_mods`]
+type ModulePath = Vec<(String, usize)>;
+
+/// A source file of the crate
+#[derive(Debug)]
+struct SourceFile {
+    path: PathBuf,
+    source: String,
+}
+
+/// The source files of a crate, found by following the `mod` declarations
+/// from the crate root. The files are read once and the module tree, which
+/// borrows them, is built by [`CrateSources::module_tree`].
+#[derive(Debug)]
+pub struct CrateSources {
+    /// The name of the crate, with `_` for `-`
+    pub name: String,
+    /// The files by the path of their module from the crate root
+    files: BTreeMap<ModulePath, SourceFile>,
+    pub diagnostics: Vec<LoadDiagnostic>,
+}
+
+impl CrateSources {
+    /// Load the crate of a directory containing a Cargo.toml, whose root is
+    /// the `[lib] path` or `src/lib.rs`
+    pub fn load(crate_dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let crate_dir = crate_dir.as_ref();
+        let manifest = std::fs::read_to_string(crate_dir.join("Cargo.toml"))?;
+        let manifest = manifest.parse::<toml::Value>()
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+        let value = |section: &str, key: &str| manifest.get(section)
+            .and_then(|section| section.get(key))
+            .and_then(|value| value.as_str())
+            .map(String::from);
+
+        let root = value("lib", "path").unwrap_or_else(|| "src/lib.rs".into());
+        let name = value("lib", "name").or_else(|| value("package", "name")).unwrap_or_default();
+        Self::load_root(name.replace('-', "_"), crate_dir.join(root))
+    }
+
+    /// Load the crate whose root file is `root`, as `src/main.rs`
+    pub fn load_root(name: String, root: impl AsRef<Path>) -> std::io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let mut sources = CrateSources { name, files: BTreeMap::new(), diagnostics: Vec::new() };
+        let source = std::fs::read_to_string(&root)?;
+        // the crate root is like a `mod.rs`, its modules are next to it
+        let dir = root.parent().map(Path::to_path_buf).unwrap_or_default();
+        sources.load_file(Vec::new(), SourceFile { path: root, source }, dir);
+        Ok(sources)
+    }
+
+    /// The paths of the files of the crate
+    pub fn paths(&self) -> impl Iterator<Item=&Path> {
+        self.files.values().map(|file| file.path.as_path())
+    }
+
+    fn load_file(&mut self, module_path: ModulePath, file: SourceFile, dir: PathBuf) {
+        let file_dir = file.path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let declarations = match Module::parse(&file.source) {
+            Ok(module) => {
+                let mut declarations = Vec::new();
+                declarations_of(&module, &module_path, &file_dir, &dir, false, &mut declarations);
+                declarations
+            },
+            Err(error) => {
+                self.diagnostics.push(LoadDiagnostic::Unparsable { path: file.path.clone(), span: error_span(&error) });
+                Vec::new()
+            },
+        };
+        let declared_in = file.path.clone();
+        self.files.insert(module_path, file);
+
+        for (module, candidates, is_path_attribute) in declarations {
+            let Some(path) = candidates.iter().find(|path| path.is_file()).cloned() else {
+                let module = names(&module);
+                self.diagnostics.push(LoadDiagnostic::MissingFile { module, declared_in: declared_in.clone(), candidates });
+                continue;
+            };
+            let is_recursive = (0..module.len())
+                .any(|len| self.files.get(&module[..len]).is_some_and(|file| file.path == path));
+            if is_recursive {
+                self.diagnostics.push(LoadDiagnostic::RecursiveModule { module: names(&module), path });
+                continue;
+            }
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(error) => {
+                    self.diagnostics.push(LoadDiagnostic::Io { path, error });
+                    continue;
+                },
+            };
+            // the modules of `foo.rs` are in `foo/`, the ones of `foo/mod.rs`
+            // and of a file named by `#[path]` are next to it
+            let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            let dir = match path.file_stem() {
+                Some(stem) if !is_path_attribute && path.file_name() != Some("mod.rs".as_ref()) => parent.join(stem),
+                _ => parent,
+            };
+            self.load_file(module, SourceFile { path, source }, dir);
+        }
+    }
+
+    /// Parse the files into the module tree of the crate, with the root
+    /// module named after the crate. The modules whose file is missing or
+    /// can't be parsed are empty.
+    pub fn module_tree(&self) -> Module<'_> {
+        let mut root = self.parse_file(&[]).unwrap_or_default();
+        root.name = self.name.clone();
+        self.attach_files(&mut root, &mut Vec::new());
+        root
+    }
+
+    fn parse_file(&self, module_path: &[(String, usize)]) -> Option<Module<'_>> {
+        let file = self.files.get(module_path)?;
+        let mut module = Module::parse(&file.source).ok()?;
+        module.file_path = file.path.display().to_string();
+        Some(module)
+    }
+
+    fn attach_files<'a>(&'a self, module: &mut Module<'a>, module_path: &mut ModulePath) {
+        let ranks = shadowed_ranks(module);
+        let children = module.mods.values_mut().map(|child| (child, 0))
+            .chain(module.shadowed_mods.iter_mut().zip(ranks));
+        for (child, rank) in children {
+            module_path.push((child.name.clone(), rank));
+            if child.is_inline {
+                child.file_path = module.file_path.clone();
+            } else if let Some(loaded) = self.parse_file(module_path) {
+                // the attributes of the `mod` item come before the inner ones
+                let mut attributes = core::mem::take(&mut child.attributes);
+                attributes.extend(loaded.attributes.iter().cloned());
+                let declaration = core::mem::replace(child, loaded);
+                child.name = declaration.name;
+                child.visibility = declaration.visibility;
+                child.attributes = attributes;
+                child.module_doc = documentation(&child.attributes);
+            }
+            self.attach_files(child, module_path);
+            module_path.pop();
+        }
+    }
+}
+
+/// The modules declared by `mod name;` in the module and its inline modules,
+/// with the files that can contain them and if they come from `#[path]`
+fn declarations_of(
+    module: &Module<'_>,
+    module_path: &[(String, usize)],
+    file_dir: &Path,
+    dir: &Path,
+    is_in_inline: bool,
+    declarations: &mut Vec<(ModulePath, Vec<PathBuf>, bool)>,
+) {
+    let children = module.mods.values().map(|child| (child, 0))
+        .chain(module.shadowed_mods.iter().zip(shadowed_ranks(module)));
+    for (child, rank) in children {
+        let name = &child.name;
+        let mut child_path = module_path.to_vec();
+        child_path.push((name.clone(), rank));
+        let path_attribute = child.attributes.iter()
+            .filter(|attribute| !attribute.is_inner)
+            .find_map(|attribute| attribute.name_value("path"));
+        if child.is_inline {
+            let child_dir = dir.join(path_attribute.as_deref().unwrap_or(name));
+            declarations_of(child, &child_path, file_dir, &child_dir, true, declarations);
+            continue;
+        }
+        let candidates = match &path_attribute {
+            // a `#[path]` is relative to the file, but in inline modules to
+            // their directory
+            Some(path) if is_in_inline => alloc::vec![dir.join(path)],
+            Some(path) => alloc::vec![file_dir.join(path)],
+            None => alloc::vec![dir.join(alloc::format!("{}.rs", name)), dir.join(name).join("mod.rs")],
+        };
+        declarations.push((child_path, candidates, path_attribute.is_some()));
+    }
+}
+
+/// The ranks of the [`Module::shadowed_mods`] among the `mod`s with their name
+fn shadowed_ranks(module: &Module<'_>) -> Vec<usize> {
+    let mut ranks = BTreeMap::new();
+    module.shadowed_mods.iter()
+        .map(|child| {
+            let rank = ranks.entry(child.name.as_str()).or_insert(0);
+            *rank += 1;
+            *rank
+        })
+        .collect()
+}
+
+fn names(module_path: &[(String, usize)]) -> Vec<String> {
+    module_path.iter().map(|(name, _)| name.clone()).collect()
+}
+
+fn error_span(error: &ParserError<'_>) -> Span {
+    match error {
+        ParserError::LexerError(LexerError::UnexpectedEndOfFile(span) | LexerError::CannotTokenize { span, .. }) => *span,
+        ParserError::UnexpectedToken(token) => token.span,
+    }
+}
+
+#[cfg(test)]
+mod test_loader {
+    use super::*;
+    use crate::CfgSet;
+
+    #[test]
+    fn load_module_tree() {
+        let crate_dir = std::env::temp_dir().join(alloc::format!("rust_parser_loader_{}", std::process::id()));
+        let files = [
+            ("Cargo.toml", "[package]\nname = \"my-crate\"\n[lib]\npath = \"source/root.rs\"\n"),
+            ("source/root.rs", "//! The crate\n/// The graph\npub mod graph;\nmod utils;\nmod inline { pub mod nested; }\nmod missing;\nmod ffi;\n\
+                #[cfg(unix)]\n#[path = \"unix.rs\"]\nmod imp;\n#[cfg(windows)]\n#[path = \"windows.rs\"]\nmod imp;\n"),
+            ("source/graph.rs", "//! Graphs\nconst LIMIT: S = S {};\nmacro_rules! m { () => {} }\nstatic mut N: u8 = 0;\n\
+                type T = u8;\nextern crate alloc;\nunsafe extern \"C\" {}\nstd::m!(1);\npub mod edges;\npub struct Graph;\n"),
+            // a function that can't be parsed is reported, not dropped
+            ("source/ffi.rs", "pub struct Before;\npub extern \"C\" fn f(x: u8, ...) {}\n"),
+            ("source/graph/edges.rs", "pub struct Edge;\n"),
+            ("source/utils/mod.rs", "#[path = \"other_name.rs\"]\nmod helpers;\n"),
+            ("source/utils/other_name.rs", "fn help() {}\n"),
+            ("source/inline/nested.rs", "pub enum Nested {}\n"),
+            ("source/unix.rs", "struct Unix;\n"),
+            ("source/windows.rs", "struct Windows;\n"),
+        ];
+        for (path, source) in files {
+            let path = crate_dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, source).unwrap();
+        }
+
+        let sources = CrateSources::load(&crate_dir).unwrap();
+        let root = sources.module_tree();
+        assert_eq!(root.name, "my_crate");
+        assert_eq!(root.module_doc, "The crate");
+        assert_eq!(sources.paths().count(), 9);
+
+        let graph = &root.mods["graph"];
+        assert!(graph.visibility.is_public() && !graph.is_inline);
+        assert_eq!(graph.module_doc, "The graph\nGraphs");
+        assert_eq!(graph.structs[0].name.name(), "Graph");
+        assert!(graph.file_path.ends_with("graph.rs"));
+        assert_eq!(graph.mods["edges"].structs[0].name.name(), "Edge");
+        assert_eq!(root.mods["utils"].mods["helpers"].functions[0].name.name(), "help");
+        assert!(root.mods["inline"].is_inline && root.mods["inline"].file_path.ends_with("root.rs"));
+        assert_eq!(root.mods["inline"].mods["nested"].enums.len(), 1);
+
+        assert!(root.mods["missing"].file_path.is_empty());
+        let [
+            LoadDiagnostic::Unparsable { path, span },
+            LoadDiagnostic::MissingFile { module, candidates, .. },
+        ] = sources.diagnostics.as_slice() else {
+            panic!("{:?}", sources.diagnostics);
+        };
+        assert!(path.ends_with("source/ffi.rs"));
+        assert_eq!(&std::fs::read_to_string(path).unwrap()[span.byte_range()], "pub");
+        assert_eq!(module, &["missing"]);
+        assert!(candidates[0].ends_with("source/missing.rs") && candidates[1].ends_with("source/missing/mod.rs"));
+
+        // both `mod imp` are loaded, the cfg picks one
+        assert_eq!(root.shadowed_mods.len(), 1);
+        let mut root = sources.module_tree();
+        root.configure(&CfgSet::for_target("x86_64-pc-windows-msvc"));
+        assert_eq!(root.mods["imp"].structs[0].name.name(), "Windows");
+        assert!(root.shadowed_mods.is_empty());
+        std::fs::remove_dir_all(crate_dir).unwrap();
+    }
+
+    #[test]
+    fn load_lexer_crate() {
+        let sources = CrateSources::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../rust_lexer")).unwrap();
+        assert!(sources.diagnostics.is_empty(), "{:?}", sources.diagnostics);
+        assert!(sources.paths().count() > 10);
+
+        let root = sources.module_tree();
+        assert_eq!(root.name, "rust_lexer");
+        assert!(root.mods.contains_key("peekable") && root.mods.contains_key("streaming"));
+        assert!(root.mods.values().all(|module| !module.file_path.is_empty()));
+        assert!(root.structs.iter().any(|structure| structure.name.name() == "Lexer"));
+    }
+}
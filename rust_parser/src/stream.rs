@@ -0,0 +1,331 @@
+//! Helpers shared by the parsers to look at and consume the tokens
+
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Identifier, Keyword, Lifetime, Peekable, Span, SpannedToken, Symbol, Token};
+
+/// The tokens of a group that was already consumed, to parse them on their own
+pub(crate) type SubStream<'a> = Peekable<SpannedToken<'a>, alloc::vec::IntoIter<SpannedToken<'a>>>;
+
+pub(crate) fn sub_stream(tokens: Vec<SpannedToken<'_>>) -> SubStream<'_> {
+    Peekable::new(tokens.into_iter())
+}
+
+/// The span from the first to the last token, `None` if there are none
+pub(crate) fn span_of(tokens: &[SpannedToken<'_>]) -> Option<Span> {
+    Some(tokens.first()?.span.join(&tokens.last()?.span))
+}
+
+/// Run the parser and go back to where the stream was if it fails
+pub(crate) fn attempt<'a, I, T>(
+    token_stream: &mut Peekable<SpannedToken<'a>, I>,
+    parse: impl FnOnce(&mut Peekable<SpannedToken<'a>, I>) -> Option<T>,
+) -> Option<T>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    let checkpoint = token_stream.checkpoint();
+    let result = parse(token_stream);
+    if result.is_some() {
+        token_stream.commit(checkpoint);
+    } else {
+        token_stream.rewind(checkpoint);
+    }
+    result
+}
+
+/// The token `index` positions ahead
+pub(crate) fn peek_token<'s, 'a, I>(token_stream: &'s mut Peekable<SpannedToken<'a>, I>, index: usize) -> Option<&'s Token<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    token_stream.peek(index).map(|token| &token.token)
+}
+
+pub(crate) fn is_symbol<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, index: usize, symbol: Symbol) -> bool
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    peek_token(token_stream, index) == Some(&Token::Symbol(symbol))
+}
+
+pub(crate) fn is_keyword<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, index: usize, keyword: Keyword) -> bool
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    peek_token(token_stream, index) == Some(&Token::Keyword(keyword))
+}
+
+/// Consume the symbol if it's next, returning its span
+pub(crate) fn eat_symbol<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, symbol: Symbol) -> Option<Span>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    if !is_symbol(token_stream, 0, symbol) {
+        return None;
+    }
+    token_stream.next().map(|token| token.span)
+}
+
+/// Consume the keyword if it's next, returning its span
+pub(crate) fn eat_keyword<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, keyword: Keyword) -> Option<Span>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    if !is_keyword(token_stream, 0, keyword) {
+        return None;
+    }
+    token_stream.next().map(|token| token.span)
+}
+
+pub(crate) fn eat_identifier<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Identifier<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    match *peek_token(token_stream, 0)? {
+        Token::Identifier(identifier) => {
+            token_stream.consume(1);
+            Some(identifier)
+        },
+        _ => None,
+    }
+}
+
+pub(crate) fn eat_lifetime<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Lifetime<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    match *peek_token(token_stream, 0)? {
+        Token::Lifetime(lifetime) => {
+            token_stream.consume(1);
+            Some(lifetime)
+        },
+        _ => None,
+    }
+}
+
+/// Consume a group delimited by `delimiter` and return the tokens inside it
+/// and the span of the whole group. `()` is a single token for the lexer.
+pub(crate) fn eat_group<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, delimiter: Delimiter) -> Option<(Vec<SpannedToken<'a>>, Span)>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    if delimiter == Delimiter::Parenthesis {
+        if let Some(span) = eat_symbol(token_stream, Symbol::EmptyTuple) {
+            return Some((Vec::new(), span));
+        }
+    }
+    attempt(token_stream, |token_stream| {
+        let open = eat_symbol(token_stream, delimiter.open_symbol())?;
+        let mut depth = 0_usize;
+        let mut tokens = Vec::new();
+        loop {
+            let token = token_stream.next()?;
+            match &token.token {
+                Token::Symbol(symbol) if Delimiter::from_open(*symbol).is_some() => depth += 1,
+                Token::Symbol(symbol) if *symbol == delimiter.close_symbol() && depth == 0 => {
+                    return Some((tokens, open.join(&token.span)));
+                },
+                Token::Symbol(symbol) if Delimiter::from_close(*symbol).is_some() => depth = depth.checked_sub(1)?,
+                _ => {},
+            }
+            tokens.push(token);
+        }
+    })
+}
+
+/// Consume the tokens up to the first one for which `stop` is true outside
+/// of any brackets, which is left in the stream as is an unmatched closing
+/// bracket. Angle brackets count as brackets when they are not inside other
+/// ones, so that the `,` of `Vec<u8, A>` doesn't stop it.
+pub(crate) fn take_until<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, stop: impl Fn(&Token<'a>) -> bool) -> Vec<SpannedToken<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    take_tokens(token_stream, stop, false)
+}
+
+/// [`take_until`] for an expression, where `<` is a comparison unless it
+/// follows `::` as in `size_of::<u8>()` or it is inside such brackets
+pub(crate) fn take_expression<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, stop: impl Fn(&Token<'a>) -> bool) -> Vec<SpannedToken<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    take_tokens(token_stream, stop, true)
+}
+
+fn take_tokens<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>, stop: impl Fn(&Token<'a>) -> bool, is_expression: bool) -> Vec<SpannedToken<'a>>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    let mut open = Vec::new();
+    let mut tokens: Vec<SpannedToken<'a>> = Vec::new();
+    while let Some(token) = token_stream.peek(0) {
+        if open.is_empty() && stop(&token.token) {
+            break;
+        }
+        let is_generic = !is_expression
+            || open.last() == Some(&Symbol::LtOrClosedAngular)
+            || tokens.last().is_some_and(|last| last.token == Token::Symbol(Symbol::PathSep));
+        match token.token {
+            Token::Symbol(Symbol::LtOrClosedAngular) if is_generic => open.push(Symbol::LtOrClosedAngular),
+            Token::Symbol(Symbol::Shl) if is_generic => open.extend([Symbol::LtOrClosedAngular; 2]),
+            Token::Symbol(Symbol::GtOrOpenAngular) if open.last() == Some(&Symbol::LtOrClosedAngular) => {
+                open.pop();
+            },
+            // the `>>` of `Vec<Vec<u8>>` closes two angle brackets
+            Token::Symbol(Symbol::Shr) if open.last() == Some(&Symbol::LtOrClosedAngular) => {
+                open.pop();
+                if open.last() == Some(&Symbol::LtOrClosedAngular) {
+                    open.pop();
+                }
+            },
+            Token::Symbol(symbol) if Delimiter::from_open(symbol).is_some() => open.push(symbol),
+            Token::Symbol(symbol) => if let Some(delimiter) = Delimiter::from_close(symbol) {
+                // an unclosed `<` inside the brackets was a comparison
+                match open.iter().rposition(|open| *open == delimiter.open_symbol()) {
+                    Some(index) => open.truncate(index),
+                    None => break,
+                }
+            },
+            _ => {},
+        }
+        // Defensive unwrap, the token was just peeked
+        tokens.push(token_stream.next().unwrap());
+    }
+    tokens
+}
+
+/// Consume the tokens of an item, returning its span. It goes on up to a `;`
+/// or a brace group outside of brackets, but after a `=` only a `;` ends it as
+/// in `const A: S = S {};`. `None` if there is no item before the end of
+/// the stream or an unmatched closing bracket.
+pub(crate) fn skip_item<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Span>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    let start = token_stream.peek(0)?.span;
+    let mut end = None;
+    let mut is_assigned = false;
+    loop {
+        let tokens = take_expression(token_stream, |token| matches!(token, Token::Symbol(Symbol::Semi | Symbol::OpenBraces)));
+        // the `=` of `#[doc = "..."]` doesn't count
+        let mut depth = 0_usize;
+        for token in &tokens {
+            match token.token {
+                Token::Symbol(symbol) if Delimiter::from_open(symbol).is_some() => depth += 1,
+                Token::Symbol(symbol) if Delimiter::from_close(symbol).is_some() => depth = depth.saturating_sub(1),
+                Token::Symbol(Symbol::Eq) if depth == 0 => is_assigned = true,
+                _ => {},
+            }
+        }
+        end = tokens.last().map(|token| token.span).or(end);
+        if let Some(semi) = eat_symbol(token_stream, Symbol::Semi) {
+            return Some(start.join(&semi));
+        }
+        let Some((_, group)) = eat_group(token_stream, Delimiter::Brace) else {
+            return Some(start.join(&end?));
+        };
+        end = Some(group);
+        if !is_assigned {
+            let end = eat_symbol(token_stream, Symbol::Semi).unwrap_or(group);
+            return Some(start.join(&end));
+        }
+    }
+}
+
+/// Consume a macro call with its attributes, as `my_macro!();` or
+/// `macro_rules! name { ... }`, returning its span. `None` if the next item
+/// is not a path followed by `!`.
+pub(crate) fn macro_call<'a, I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<Span>
+where
+    I: Iterator<Item=SpannedToken<'a>>,
+{
+    attempt(token_stream, |token_stream| {
+        let start = token_stream.peek(0)?.span;
+        crate::attributes(token_stream);
+        eat_symbol(token_stream, Symbol::PathSep);
+        eat_identifier(token_stream)?;
+        while eat_symbol(token_stream, Symbol::PathSep).is_some() {
+            eat_identifier(token_stream)?;
+        }
+        eat_symbol(token_stream, Symbol::Not)?;
+        // the name of `macro_rules! name`
+        eat_identifier(token_stream);
+        let is_group = match *peek_token(token_stream, 0)? {
+            Token::Symbol(symbol) => symbol == Symbol::EmptyTuple || Delimiter::from_open(symbol).is_some(),
+            _ => false,
+        };
+        if !is_group {
+            return None;
+        }
+        Some(start.join(&skip_item(token_stream)?))
+    })
+}
+
+/// Split the tokens at the `separator`s outside of brackets, without an
+/// empty last part for a trailing separator
+pub(crate) fn split(tokens: Vec<SpannedToken<'_>>, separator: Symbol) -> Vec<Vec<SpannedToken<'_>>> {
+    let mut token_stream = sub_stream(tokens);
+    let mut parts = Vec::new();
+    while token_stream.peek(0).is_some() {
+        let mut part = take_until(&mut token_stream, |token| *token == Token::Symbol(separator));
+        if eat_symbol(&mut token_stream, separator).is_none() {
+            // stopped at an unmatched closing bracket, keep it in the part
+            part.extend(token_stream.by_ref());
+        }
+        parts.push(part);
+    }
+    parts
+}
+
+/// Split `<<` and `>>` in two angle brackets, the lexer can't tell them
+/// from shifts but in types as `Vec<Vec<u8>>` they are two brackets. The
+/// ones in the expressions of a type, as the length of `[u8; 1 << 4]` or a
+/// `{ 1 << 4 }` const argument, are shifts and are kept.
+pub(crate) fn split_shifts(tokens: Vec<SpannedToken<'_>>) -> Vec<SpannedToken<'_>> {
+    // if each open bracket holds an expression
+    let mut open = Vec::new();
+    let mut result = Vec::new();
+    for token in tokens {
+        let is_expression = open.last() == Some(&true);
+        let angle = match token.token {
+            Token::Symbol(Symbol::OpenBraces) => {
+                open.push(true);
+                None
+            },
+            Token::Symbol(symbol) if Delimiter::from_open(symbol).is_some() => {
+                open.push(is_expression);
+                None
+            },
+            Token::Symbol(symbol) if Delimiter::from_close(symbol).is_some() => {
+                open.pop();
+                None
+            },
+            // the length of an array
+            Token::Symbol(Symbol::Semi) => {
+                if let Some(last) = open.last_mut() {
+                    *last = true;
+                }
+                None
+            },
+            Token::Symbol(Symbol::Shl) if !is_expression => Some(Symbol::LtOrClosedAngular),
+            Token::Symbol(Symbol::Shr) if !is_expression => Some(Symbol::GtOrOpenAngular),
+            _ => None,
+        };
+        let Some(angle) = angle else {
+            result.push(token);
+            continue;
+        };
+        let (first, second) = split_shift(token.span);
+        result.push(SpannedToken { token: Token::Symbol(angle), span: first });
+        result.push(SpannedToken { token: Token::Symbol(angle), span: second });
+    }
+    result
+}
+
+/// The spans of the two brackets of a `<<` or a `>>`
+pub(crate) fn split_shift(span: Span) -> (Span, Span) {
+    let mut middle = span.start;
+    middle.advance("<");
+    (Span { end: middle, ..span }, Span { start: middle, ..span })
+}
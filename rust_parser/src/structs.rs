@@ -0,0 +1,218 @@
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Identifier, Keyword, Peekable, Span, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{attributes, documentation, Attribute, FromTokenStream, Generics, Type, Visibility};
+
+/// A field of a struct, a union or an enum variant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    /// `None` for the fields of a tuple
+    pub name: Option<Identifier<'a>>,
+    pub ty: Type<'a>,
+    pub span: Span,
+}
+
+impl<'a> Field<'a> {
+    /// The doc comments of the field
+    pub fn docs(&self) -> alloc::string::String {
+        documentation(&self.attributes)
+    }
+
+    fn from_tokens(tokens: Vec<SpannedToken<'a>>, is_named: bool) -> Option<Self> {
+        let span = span_of(&tokens)?;
+        let mut token_stream = sub_stream(tokens);
+        let attributes = attributes(&mut token_stream);
+        let visibility = Visibility::from_tokens_stream(&mut token_stream)?;
+        let name = if is_named {
+            let name = eat_identifier(&mut token_stream)?;
+            eat_symbol(&mut token_stream, Symbol::Colon)?;
+            Some(name)
+        } else {
+            None
+        };
+        let ty = Type::from_tokens(token_stream.collect())?;
+        Some(Field { attributes, visibility, name, ty, span })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fields<'a> {
+    /// `{ a: u8, b: u8 }`
+    Named(Vec<Field<'a>>),
+    /// `(u8, u8)`
+    Tuple(Vec<Field<'a>>),
+    Unit,
+}
+
+impl<'a> Fields<'a> {
+    pub fn iter(&self) -> core::slice::Iter<'_, Field<'a>> {
+        match self {
+            Fields::Named(fields) | Fields::Tuple(fields) => fields.iter(),
+            Fields::Unit => [].iter(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Parse the fields of the group if there is one, returning the span of
+    /// the group too. `()` and `{}` are a tuple and named fields with no fields.
+    pub(crate) fn from_tokens_stream<I>(token_stream: &mut Peekable<SpannedToken<'a>, I>) -> Option<(Self, Option<Span>)>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        let (fields, span, is_named) = if let Some((fields, span)) = eat_group(token_stream, Delimiter::Brace) {
+            (fields, span, true)
+        } else if let Some((fields, span)) = eat_group(token_stream, Delimiter::Parenthesis) {
+            (fields, span, false)
+        } else {
+            return Some((Fields::Unit, None));
+        };
+        let fields = split(fields, Symbol::Comma).into_iter()
+            .map(|field| Field::from_tokens(field, is_named))
+            .collect::<Option<Vec<_>>>()?;
+        let fields = if is_named { Fields::Named(fields) } else { Fields::Tuple(fields) };
+        Some((fields, Some(span)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    pub name: Identifier<'a>,
+    pub generics: Generics<'a>,
+    pub fields: Fields<'a>,
+    pub span: Span,
+}
+
+impl<'a> FromTokenStream<'a> for Struct<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            eat_keyword(token_stream, Keyword::Struct)?;
+            let name = eat_identifier(token_stream)?;
+            let mut generics = Generics::from_tokens_stream(token_stream)?;
+            // the where clause goes before named fields but after tuple ones
+            generics.where_clause(token_stream)?;
+            let (fields, group) = Fields::from_tokens_stream(token_stream)?;
+            let end = match (&fields, group) {
+                (Fields::Named(_), Some(group)) => group,
+                _ => {
+                    generics.where_clause(token_stream)?;
+                    eat_symbol(token_stream, Symbol::Semi)?
+                },
+            };
+            Some(Struct { attributes, visibility, name, generics, fields, span: start.join(&end) })
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Union<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    pub name: Identifier<'a>,
+    pub generics: Generics<'a>,
+    pub fields: Vec<Field<'a>>,
+    pub span: Span,
+}
+
+impl<'a> FromTokenStream<'a> for Union<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            // `union` is only a keyword before the name of a union
+            let is_union = matches!(peek_token(token_stream, 0), Some(Token::Identifier(identifier)) if identifier.name() == "union")
+                && matches!(peek_token(token_stream, 1), Some(Token::Identifier(_)));
+            if !is_union {
+                return None;
+            }
+            token_stream.consume(1);
+            let name = eat_identifier(token_stream)?;
+            let mut generics = Generics::from_tokens_stream(token_stream)?;
+            generics.where_clause(token_stream)?;
+            let (fields, end) = eat_group(token_stream, Delimiter::Brace)?;
+            let fields = split(fields, Symbol::Comma).into_iter()
+                .map(|field| Field::from_tokens(field, true))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Union { attributes, visibility, name, generics, fields, span: start.join(&end) })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_structs {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use crate::Module;
+
+    #[test]
+    fn parse_structs() {
+        let source = "
+            #[derive(Debug, Clone, serde::Serialize)]
+            pub struct Named<T> where T: Clone {
+                /// The first
+                /// field
+                pub first: Vec<T>,
+                #[allow(dead_code)]
+                pub(crate) second: [u8; 4],
+                third: Option<(u8, u8)>,
+            }
+            struct Tuple<'a>(pub &'a str, u8) where Self: Sized;
+            pub struct Unit;
+            #[repr(C)]
+            union Bits { integer: u32, float: f32 }
+        ";
+        let module = Module::parse(source).unwrap();
+        let [named, tuple, unit] = module.structs.as_slice() else {
+            panic!("{:?}", module.structs);
+        };
+
+        let derives = named.attributes.iter().flat_map(|attribute| attribute.derives()).collect::<Vec<_>>();
+        assert_eq!(derives, ["Debug", "Clone", "Serialize"]);
+        assert_eq!(named.generics.where_clause.len(), 1);
+        let Fields::Named(fields) = &named.fields else { panic!("{:?}", named.fields) };
+        let fields = fields.iter()
+            .map(|field| (field.name.unwrap().name(), field.visibility.clone(), field.ty.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(fields, [
+            ("first", Visibility::Public, String::from("Vec<T>")),
+            ("second", Visibility::PublicCrate, String::from("[u8; 4]")),
+            ("third", Visibility::Private, String::from("Option<(u8, u8)>")),
+        ]);
+        let first = named.fields.iter().next().unwrap();
+        assert_eq!(first.docs(), "The first\nfield");
+        assert!(source[first.span.byte_range()].starts_with("/// The first") && source[first.span.byte_range()].ends_with("Vec<T>"));
+        assert!(source[named.span.byte_range()].ends_with("}"));
+
+        assert!(matches!(&tuple.fields, Fields::Tuple(fields) if fields.len() == 2 && fields[0].visibility == Visibility::Public));
+        assert_eq!(tuple.generics.where_clause.len(), 1);
+        assert!(source[tuple.span.byte_range()].ends_with("Sized;"));
+        assert_eq!(unit.fields, Fields::Unit);
+
+        let [bits] = module.unions.as_slice() else { panic!("{:?}", module.unions) };
+        assert_eq!(bits.fields.iter().map(|field| field.name.unwrap().name()).collect::<Vec<_>>(), ["integer", "float"]);
+    }
+}
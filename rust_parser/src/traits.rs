@@ -0,0 +1,69 @@
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Identifier, Keyword, Peekable, Span, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{attributes, bounds, AssociatedConst, AssociatedItems, AssociatedType, Attribute, Bound, FromTokenStream, Function, Generics, Visibility};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trait<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub visibility: Visibility<'a>,
+    pub is_unsafe: bool,
+    pub is_auto: bool,
+    pub name: Identifier<'a>,
+    pub generics: Generics<'a>,
+    /// The bounds after `:`, as in `trait Ord: Eq + PartialOrd`
+    pub supertraits: Vec<Bound<'a>>,
+    pub consts: Vec<AssociatedConst<'a>>,
+    pub types: Vec<AssociatedType<'a>>,
+    /// The methods with a body are the default ones
+    pub methods: Vec<Function<'a>>,
+    pub span: Span,
+}
+
+impl<'a> FromTokenStream<'a> for Trait<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            let is_unsafe = eat_keyword(token_stream, Keyword::Unsafe).is_some();
+            // `auto` is only a keyword before `trait`
+            let is_auto = matches!(peek_token(token_stream, 0), Some(Token::Identifier(identifier)) if identifier.name() == "auto")
+                && is_keyword(token_stream, 1, Keyword::Trait);
+            if is_auto {
+                token_stream.consume(1);
+            }
+            eat_keyword(token_stream, Keyword::Trait)?;
+            let name = eat_identifier(token_stream)?;
+            let mut generics = Generics::from_tokens_stream(token_stream)?;
+            let supertraits = match eat_symbol(token_stream, Symbol::Colon) {
+                Some(_) => bounds(take_until(token_stream, |token| matches!(
+                    token, Token::Symbol(Symbol::OpenBraces) | Token::Keyword(Keyword::Where)
+                )))?,
+                None => Vec::new(),
+            };
+            generics.where_clause(token_stream)?;
+
+            let (items, end) = eat_group(token_stream, Delimiter::Brace)?;
+            let AssociatedItems { consts, types, methods } = AssociatedItems::from_tokens(items)?;
+            Some(Trait {
+                attributes,
+                visibility,
+                is_unsafe,
+                is_auto,
+                name,
+                generics,
+                supertraits,
+                consts,
+                types,
+                methods,
+                span: start.join(&end),
+            })
+        })
+    }
+}
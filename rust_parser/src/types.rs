@@ -0,0 +1,690 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use rust_lexer::{Delimiter, Keyword, Lexer, Lifetime, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{angle_brackets, bounds, for_lifetimes, parse_bounds, Bound, FromTokenStream, FunctionQualifiers};
+
+/// The names that `Primitive` stands for in the patterns of [`Type::matches`]
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "f32", "f64",
+    "u8", "u16", "u32", "u64", "u128", "usize",
+    "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type<'a> {
+    /// `std::vec::Vec<T>`, `u8` or `Self`
+    Path(TypePath<'a>),
+    /// `<T as Trait>::Item`, the trait is optional as in `<T>::Item`
+    QualifiedPath {
+        self_type: Box<Type<'a>>,
+        trait_path: Option<TypePath<'a>>,
+        segments: Vec<PathSegment<'a>>,
+    },
+    /// `&'a mut T`
+    Reference {
+        lifetime: Option<Lifetime<'a>>,
+        is_mutable: bool,
+        ty: Box<Type<'a>>,
+    },
+    /// `*const T` or `*mut T`
+    Pointer {
+        is_mutable: bool,
+        ty: Box<Type<'a>>,
+    },
+    /// `[T; N]`, the length is kept as the tokens of the expression
+    Array {
+        ty: Box<Type<'a>>,
+        len: Vec<SpannedToken<'a>>,
+    },
+    /// `[T]`
+    Slice(Box<Type<'a>>),
+    /// `(A, B)`, `()` is the empty tuple
+    Tuple(Vec<Type<'a>>),
+    /// `for<'a> unsafe extern "C" fn(&'a u8, ...) -> u8`
+    Function {
+        for_lifetimes: Vec<Lifetime<'a>>,
+        qualifiers: FunctionQualifiers<'a>,
+        arguments: Vec<Type<'a>>,
+        is_variadic: bool,
+        return_type: Option<Box<Type<'a>>>,
+    },
+    /// `dyn Trait + 'a`
+    TraitObject(Vec<Bound<'a>>),
+    /// `impl Trait + 'a`
+    ImplTrait(Vec<Bound<'a>>),
+    /// `!`
+    Never,
+    /// `_`
+    Infer,
+    /// `path!(tokens)`
+    Macro {
+        path: TypePath<'a>,
+        delimiter: Delimiter,
+        tokens: Vec<SpannedToken<'a>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePath<'a> {
+    /// If it starts with `::`
+    pub is_global: bool,
+    pub segments: Vec<PathSegment<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSegment<'a> {
+    /// An identifier or one of `Self`, `self`, `super` and `crate`
+    pub name: &'a str,
+    pub arguments: PathArguments<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathArguments<'a> {
+    None,
+    /// `<'a, T, Item = U>`
+    AngleBracketed(Vec<GenericArgument<'a>>),
+    /// `(A, B) -> C` of the `Fn` traits
+    Parenthesized {
+        inputs: Vec<Type<'a>>,
+        output: Option<Box<Type<'a>>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenericArgument<'a> {
+    Lifetime(Lifetime<'a>),
+    Type(Type<'a>),
+    /// A literal or a block as `{ N + 1 }`
+    Const(Vec<SpannedToken<'a>>),
+    /// `Item = T`
+    Binding {
+        name: &'a str,
+        ty: Type<'a>,
+    },
+    /// `Item: Bound`
+    Constraint {
+        name: &'a str,
+        bounds: Vec<Bound<'a>>,
+    },
+}
+
+impl<'a> Type<'a> {
+    /// Lex and parse a type, `None` if the source is not exactly one type
+    pub fn parse(source: &'a str) -> Option<Self> {
+        let mut tokens = Lexer::new(source).spanned();
+        let type_tokens = tokens.by_ref().filter(|token| !token.token.is_trivia()).collect();
+        if !tokens.diagnostics().is_empty() {
+            return None;
+        }
+        Type::from_tokens(type_tokens)
+    }
+
+    /// The type made of all the tokens, `None` if they are not exactly one type
+    pub(crate) fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Option<Self> {
+        let mut token_stream = sub_stream(split_shifts(tokens));
+        let ty = parse_type(&mut token_stream)?;
+        token_stream.peek(0).is_none().then_some(ty)
+    }
+
+    /// If it's one of the primitive types, as `u8` or `str`
+    pub fn is_primitive(&self) -> bool {
+        match self {
+            Type::Path(TypePath { is_global: false, segments }) => matches!(
+                segments.as_slice(),
+                [PathSegment { name, arguments: PathArguments::None }] if PRIMITIVES.contains(name)
+            ),
+            _ => false,
+        }
+    }
+
+    /// Match the type against a pattern with the same structure, where `_`
+    /// matches any type and `Primitive` any primitive type. The path of the
+    /// pattern only has to be the end of the one of the type, so that `Vec<_>`
+    /// matches `std::vec::Vec<u8>`, and lifetimes are only compared when the
+    /// pattern has them.
+    pub fn matches(&self, pattern: &Type<'_>) -> bool {
+        match (self, pattern) {
+            (_, Type::Infer) => true,
+            (ty, Type::Path(path)) if path.is_ident("Primitive") => ty.is_primitive(),
+            (Type::Path(path), Type::Path(pattern)) => path.matches(pattern),
+            (
+                Type::QualifiedPath { self_type, trait_path, segments },
+                Type::QualifiedPath { self_type: pattern_self, trait_path: pattern_trait, segments: pattern_segments },
+            ) => {
+                self_type.matches(pattern_self)
+                    && match (trait_path, pattern_trait) {
+                        (Some(path), Some(pattern)) => path.matches(pattern),
+                        (None, None) => true,
+                        _ => false,
+                    }
+                    && segments.len() == pattern_segments.len()
+                    && segments.iter().zip(pattern_segments).all(|(segment, pattern)| segment.matches(pattern))
+            },
+            (
+                Type::Reference { lifetime, is_mutable, ty },
+                Type::Reference { lifetime: pattern_lifetime, is_mutable: pattern_mutable, ty: pattern },
+            ) => {
+                is_mutable == pattern_mutable
+                    && pattern_lifetime.is_none_or(|pattern| lifetime.is_some_and(|lifetime| lifetime.name() == pattern.name()))
+                    && ty.matches(pattern)
+            },
+            (Type::Pointer { is_mutable, ty }, Type::Pointer { is_mutable: pattern_mutable, ty: pattern }) => {
+                is_mutable == pattern_mutable && ty.matches(pattern)
+            },
+            (Type::Array { ty, len }, Type::Array { ty: pattern, len: pattern_len }) => {
+                ty.matches(pattern) && same_tokens(len, pattern_len)
+            },
+            (Type::Slice(ty), Type::Slice(pattern)) => ty.matches(pattern),
+            (Type::Tuple(types), Type::Tuple(patterns)) => all_match(types, patterns),
+            (
+                Type::Function { for_lifetimes: _, qualifiers, arguments, is_variadic, return_type },
+                Type::Function {
+                    for_lifetimes: _,
+                    qualifiers: pattern_qualifiers,
+                    arguments: pattern_arguments,
+                    is_variadic: pattern_variadic,
+                    return_type: pattern_return,
+                },
+            ) => {
+                qualifiers.is_unsafe == pattern_qualifiers.is_unsafe
+                    && qualifiers.abi == pattern_qualifiers.abi
+                    && is_variadic == pattern_variadic
+                    && all_match(arguments, pattern_arguments)
+                    && optional_match(return_type.as_deref(), pattern_return.as_deref())
+            },
+            (Type::TraitObject(bounds), Type::TraitObject(patterns))
+            | (Type::ImplTrait(bounds), Type::ImplTrait(patterns)) => {
+                bounds.len() == patterns.len()
+                    && bounds.iter().zip(patterns).all(|(bound, pattern)| bound.matches(pattern))
+            },
+            (Type::Never, Type::Never) => true,
+            (Type::Macro { path, tokens, .. }, Type::Macro { path: pattern, tokens: pattern_tokens, .. }) => {
+                path.matches(pattern) && same_tokens(tokens, pattern_tokens)
+            },
+            _ => false,
+        }
+    }
+
+    /// [`Type::matches`] with a pattern to parse, one that doesn't parse
+    /// matches nothing
+    pub fn matches_str(&self, pattern: &str) -> bool {
+        Type::parse(pattern).is_some_and(|pattern| self.matches(&pattern))
+    }
+}
+
+impl<'a> TypePath<'a> {
+    /// If it's the single identifier `name` without generics
+    pub fn is_ident(&self, name: &str) -> bool {
+        matches!(
+            self.segments.as_slice(),
+            [segment] if !self.is_global && segment.name == name && segment.arguments == PathArguments::None
+        )
+    }
+
+    pub fn last(&self) -> Option<&PathSegment<'a>> {
+        self.segments.last()
+    }
+
+    /// If the pattern is the end of the path, see [`Type::matches`]
+    pub fn matches(&self, pattern: &TypePath<'_>) -> bool {
+        let Some(skip) = self.segments.len().checked_sub(pattern.segments.len()) else {
+            return false;
+        };
+        (!pattern.is_global || (self.is_global && skip == 0))
+            && self.segments[skip..].iter().zip(&pattern.segments).all(|(segment, pattern)| segment.matches(pattern))
+    }
+}
+
+impl<'a> PathSegment<'a> {
+    fn matches(&self, pattern: &PathSegment<'_>) -> bool {
+        self.name == pattern.name && match (&self.arguments, &pattern.arguments) {
+            (PathArguments::None, PathArguments::None) => true,
+            (PathArguments::AngleBracketed(arguments), PathArguments::AngleBracketed(patterns)) => {
+                arguments.len() == patterns.len()
+                    && arguments.iter().zip(patterns).all(|(argument, pattern)| argument.matches(pattern))
+            },
+            (
+                PathArguments::Parenthesized { inputs, output },
+                PathArguments::Parenthesized { inputs: pattern_inputs, output: pattern_output },
+            ) => all_match(inputs, pattern_inputs) && optional_match(output.as_deref(), pattern_output.as_deref()),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> GenericArgument<'a> {
+    fn matches(&self, pattern: &GenericArgument<'_>) -> bool {
+        match (self, pattern) {
+            (GenericArgument::Lifetime(lifetime), GenericArgument::Lifetime(pattern)) => lifetime.name() == pattern.name(),
+            (GenericArgument::Type(ty), GenericArgument::Type(pattern)) => ty.matches(pattern),
+            (GenericArgument::Const(tokens), GenericArgument::Const(pattern)) => same_tokens(tokens, pattern),
+            (GenericArgument::Binding { name, ty }, GenericArgument::Binding { name: pattern_name, ty: pattern }) => {
+                name == pattern_name && ty.matches(pattern)
+            },
+            (
+                GenericArgument::Constraint { name, bounds },
+                GenericArgument::Constraint { name: pattern_name, bounds: patterns },
+            ) => {
+                name == pattern_name
+                    && bounds.len() == patterns.len()
+                    && bounds.iter().zip(patterns).all(|(bound, pattern)| bound.matches(pattern))
+            },
+            // `_` is parsed as a type
+            (_, GenericArgument::Type(Type::Infer)) => true,
+            _ => false,
+        }
+    }
+}
+
+fn all_match(types: &[Type<'_>], patterns: &[Type<'_>]) -> bool {
+    types.len() == patterns.len() && types.iter().zip(patterns).all(|(ty, pattern)| ty.matches(pattern))
+}
+
+fn optional_match(ty: Option<&Type<'_>>, pattern: Option<&Type<'_>>) -> bool {
+    match (ty, pattern) {
+        (Some(ty), Some(pattern)) => ty.matches(pattern),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn same_tokens(tokens: &[SpannedToken<'_>], pattern: &[SpannedToken<'_>]) -> bool {
+    tokens.len() == pattern.len() && tokens.iter().zip(pattern).all(|(token, pattern)| token.token == pattern.token)
+}
+
+/// Parse one type at the start of the stream
+pub(crate) fn parse_type<'a>(token_stream: &mut SubStream<'a>) -> Option<Type<'a>> {
+    match peek_token(token_stream, 0)? {
+        Token::Symbol(Symbol::Not) => {
+            token_stream.consume(1);
+            Some(Type::Never)
+        },
+        Token::Symbol(Symbol::Underscore) => {
+            token_stream.consume(1);
+            Some(Type::Infer)
+        },
+        Token::Symbol(Symbol::EmptyTuple | Symbol::OpenParenthesis) => {
+            let (tokens, _) = eat_group(token_stream, Delimiter::Parenthesis)?;
+            let has_trailing_comma = tokens.last().is_some_and(|token| token.token == Token::Symbol(Symbol::Comma));
+            let mut types = split(tokens, Symbol::Comma).into_iter()
+                .map(Type::from_tokens)
+                .collect::<Option<Vec<_>>>()?;
+            // `(T)` is only parenthesized, `(T,)` is a tuple
+            if types.len() == 1 && !has_trailing_comma {
+                return types.pop();
+            }
+            Some(Type::Tuple(types))
+        },
+        Token::Symbol(Symbol::OpenBraket) => {
+            let (tokens, _) = eat_group(token_stream, Delimiter::Bracket)?;
+            let mut inner = sub_stream(tokens);
+            let ty = Box::new(parse_type(&mut inner)?);
+            if eat_symbol(&mut inner, Symbol::Semi).is_none() {
+                return inner.peek(0).is_none().then_some(Type::Slice(ty));
+            }
+            let len = inner.collect::<Vec<_>>();
+            (!len.is_empty()).then_some(Type::Array { ty, len })
+        },
+        Token::Symbol(Symbol::And) => {
+            token_stream.consume(1);
+            reference(token_stream)
+        },
+        Token::Symbol(Symbol::AndAnd) => {
+            // `&&T` is a reference to a reference
+            token_stream.consume(1);
+            let ty = Box::new(reference(token_stream)?);
+            Some(Type::Reference { lifetime: None, is_mutable: false, ty })
+        },
+        Token::Symbol(Symbol::Star) => {
+            token_stream.consume(1);
+            let is_mutable = match peek_token(token_stream, 0)? {
+                Token::Keyword(Keyword::Const) => false,
+                Token::Keyword(Keyword::Mut) => true,
+                _ => return None,
+            };
+            token_stream.consume(1);
+            let ty = Box::new(parse_type(token_stream)?);
+            Some(Type::Pointer { is_mutable, ty })
+        },
+        Token::Symbol(Symbol::LtOrClosedAngular) => qualified_path(token_stream),
+        Token::Keyword(Keyword::Dyn) => {
+            token_stream.consume(1);
+            Some(Type::TraitObject(parse_bounds(token_stream)?))
+        },
+        Token::Keyword(Keyword::Impl) => {
+            token_stream.consume(1);
+            Some(Type::ImplTrait(parse_bounds(token_stream)?))
+        },
+        Token::Keyword(Keyword::For | Keyword::Unsafe | Keyword::Extern | Keyword::Fn) => function_pointer(token_stream),
+        _ => {
+            let path = parse_path(token_stream)?;
+            if eat_symbol(token_stream, Symbol::Not).is_none() {
+                return Some(Type::Path(path));
+            }
+            [Delimiter::Parenthesis, Delimiter::Bracket, Delimiter::Brace].into_iter()
+                .find_map(|delimiter| {
+                    let (tokens, _) = eat_group(token_stream, delimiter)?;
+                    Some(Type::Macro { path: path.clone(), delimiter, tokens })
+                })
+        },
+    }
+}
+
+/// The rest of a reference after the `&`
+fn reference<'a>(token_stream: &mut SubStream<'a>) -> Option<Type<'a>> {
+    let lifetime = eat_lifetime(token_stream);
+    let is_mutable = eat_keyword(token_stream, Keyword::Mut).is_some();
+    let ty = Box::new(parse_type(token_stream)?);
+    Some(Type::Reference { lifetime, is_mutable, ty })
+}
+
+fn qualified_path<'a>(token_stream: &mut SubStream<'a>) -> Option<Type<'a>> {
+    eat_symbol(token_stream, Symbol::LtOrClosedAngular)?;
+    let self_type = Box::new(parse_type(token_stream)?);
+    let trait_path = match eat_keyword(token_stream, Keyword::As) {
+        Some(_) => Some(parse_path(token_stream)?),
+        None => None,
+    };
+    eat_symbol(token_stream, Symbol::GtOrOpenAngular)?;
+    eat_symbol(token_stream, Symbol::PathSep)?;
+    let TypePath { segments, .. } = parse_path(token_stream)?;
+    Some(Type::QualifiedPath { self_type, trait_path, segments })
+}
+
+fn function_pointer<'a>(token_stream: &mut SubStream<'a>) -> Option<Type<'a>> {
+    let for_lifetimes = for_lifetimes(token_stream)?;
+    let qualifiers = FunctionQualifiers::from_tokens_stream(token_stream)?;
+    eat_keyword(token_stream, Keyword::Fn)?;
+    let (tokens, _) = eat_group(token_stream, Delimiter::Parenthesis)?;
+    let mut arguments = split(tokens, Symbol::Comma);
+    let is_variadic = arguments.last()
+        .and_then(|argument| argument.last())
+        .is_some_and(|token| token.token == Token::Symbol(Symbol::DotDotDot));
+    if is_variadic {
+        arguments.pop();
+    }
+    let arguments = arguments.into_iter()
+        .map(|mut argument| {
+            // the arguments can be named as `fn(x: u8)`
+            if matches!(
+                argument.get(..2),
+                Some([SpannedToken { token: Token::Identifier(_) | Token::Symbol(Symbol::Underscore), .. }, SpannedToken { token: Token::Symbol(Symbol::Colon), .. }])
+            ) {
+                argument.drain(..2);
+            }
+            Type::from_tokens(argument)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let return_type = match eat_symbol(token_stream, Symbol::RArrow) {
+        Some(_) => Some(Box::new(parse_type(token_stream)?)),
+        None => None,
+    };
+    Some(Type::Function { for_lifetimes, qualifiers, arguments, is_variadic, return_type })
+}
+
+/// Parse a path as `::std::vec::Vec<u8>` or `Fn(u8) -> u8`
+pub(crate) fn parse_path<'a>(token_stream: &mut SubStream<'a>) -> Option<TypePath<'a>> {
+    let is_global = eat_symbol(token_stream, Symbol::PathSep).is_some();
+    let mut segments = Vec::new();
+    loop {
+        let name = match *peek_token(token_stream, 0)? {
+            Token::Identifier(identifier) => identifier.name(),
+            Token::Keyword(keyword @ (Keyword::SelfCapitalized | Keyword::SelfLowercase | Keyword::Super | Keyword::Crate)) => keyword.into(),
+            _ => return None,
+        };
+        token_stream.consume(1);
+
+        // the turbofish is allowed in types too
+        if is_symbol(token_stream, 0, Symbol::PathSep) && is_symbol(token_stream, 1, Symbol::LtOrClosedAngular) {
+            token_stream.consume(1);
+        }
+        let arguments = if is_symbol(token_stream, 0, Symbol::LtOrClosedAngular) {
+            let arguments = angle_brackets(token_stream)?;
+            PathArguments::AngleBracketed(split(arguments, Symbol::Comma).into_iter()
+                .map(generic_argument)
+                .collect::<Option<Vec<_>>>()?)
+        } else if let Some((inputs, _)) = eat_group(token_stream, Delimiter::Parenthesis) {
+            let inputs = split(inputs, Symbol::Comma).into_iter()
+                .map(Type::from_tokens)
+                .collect::<Option<Vec<_>>>()?;
+            let output = match eat_symbol(token_stream, Symbol::RArrow) {
+                Some(_) => Some(Box::new(parse_type(token_stream)?)),
+                None => None,
+            };
+            PathArguments::Parenthesized { inputs, output }
+        } else {
+            PathArguments::None
+        };
+        segments.push(PathSegment { name, arguments });
+
+        let continues = is_symbol(token_stream, 0, Symbol::PathSep)
+            && matches!(peek_token(token_stream, 1), Some(Token::Identifier(_) | Token::Keyword(_)));
+        if !continues {
+            return Some(TypePath { is_global, segments });
+        }
+        token_stream.consume(1);
+    }
+}
+
+fn generic_argument(tokens: Vec<SpannedToken<'_>>) -> Option<GenericArgument<'_>> {
+    match tokens.as_slice() {
+        [SpannedToken { token: Token::Lifetime(lifetime), .. }] => return Some(GenericArgument::Lifetime(*lifetime)),
+        [SpannedToken { token: Token::Literal(_) | Token::Keyword(Keyword::True | Keyword::False), .. }]
+        | [SpannedToken { token: Token::Symbol(Symbol::Minus), .. }, SpannedToken { token: Token::Literal(_), .. }]
+        | [SpannedToken { token: Token::Symbol(Symbol::OpenBraces), .. }, ..] => return Some(GenericArgument::Const(tokens)),
+        [SpannedToken { token: Token::Identifier(name), .. }, SpannedToken { token: Token::Symbol(Symbol::Eq), .. }, ..] => {
+            let name = name.name();
+            let ty = Type::from_tokens(tokens.into_iter().skip(2).collect())?;
+            return Some(GenericArgument::Binding { name, ty });
+        },
+        [SpannedToken { token: Token::Identifier(name), .. }, SpannedToken { token: Token::Symbol(Symbol::Colon), .. }, ..] => {
+            let name = name.name();
+            let bounds = bounds(tokens.into_iter().skip(2).collect())?;
+            return Some(GenericArgument::Constraint { name, bounds });
+        },
+        _ => {},
+    }
+    Type::from_tokens(tokens).map(GenericArgument::Type)
+}
+
+/// Write the tokens with a space where there was whitespace between them
+pub(crate) fn write_tokens(f: &mut fmt::Formatter<'_>, tokens: &[SpannedToken<'_>]) -> fmt::Result {
+    for (index, token) in tokens.iter().enumerate() {
+        if index > 0 && tokens[index - 1].span.end.byte_offset != token.span.start.byte_offset {
+            f.write_str(" ")?;
+        }
+        write!(f, "{}", token.token)?;
+    }
+    Ok(())
+}
+
+fn write_separated<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T], separator: &str) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Type<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Path(path) => write!(f, "{}", path),
+            Type::QualifiedPath { self_type, trait_path, segments } => {
+                write!(f, "<{}", self_type)?;
+                if let Some(trait_path) = trait_path {
+                    write!(f, " as {}", trait_path)?;
+                }
+                f.write_str(">::")?;
+                write_separated(f, segments, "::")
+            },
+            Type::Reference { lifetime, is_mutable, ty } => {
+                f.write_str("&")?;
+                if let Some(lifetime) = lifetime {
+                    write!(f, "{} ", lifetime)?;
+                }
+                if *is_mutable {
+                    f.write_str("mut ")?;
+                }
+                write!(f, "{}", ty)
+            },
+            Type::Pointer { is_mutable, ty } => write!(f, "*{} {}", if *is_mutable { "mut" } else { "const" }, ty),
+            Type::Array { ty, len } => {
+                write!(f, "[{}; ", ty)?;
+                write_tokens(f, len)?;
+                f.write_str("]")
+            },
+            Type::Slice(ty) => write!(f, "[{}]", ty),
+            Type::Tuple(types) => {
+                f.write_str("(")?;
+                write_separated(f, types, ", ")?;
+                if types.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            },
+            Type::Function { for_lifetimes, qualifiers, arguments, is_variadic, return_type } => {
+                if !for_lifetimes.is_empty() {
+                    f.write_str("for<")?;
+                    write_separated(f, for_lifetimes, ", ")?;
+                    f.write_str("> ")?;
+                }
+                if qualifiers.is_unsafe {
+                    f.write_str("unsafe ")?;
+                }
+                if qualifiers.is_extern {
+                    f.write_str("extern ")?;
+                    if let Some(abi) = qualifiers.abi {
+                        write!(f, "\"{}\" ", abi)?;
+                    }
+                }
+                f.write_str("fn(")?;
+                write_separated(f, arguments, ", ")?;
+                if *is_variadic {
+                    f.write_str(if arguments.is_empty() { "..." } else { ", ..." })?;
+                }
+                f.write_str(")")?;
+                if let Some(return_type) = return_type {
+                    write!(f, " -> {}", return_type)?;
+                }
+                Ok(())
+            },
+            Type::TraitObject(bounds) => {
+                f.write_str("dyn ")?;
+                write_separated(f, bounds, " + ")
+            },
+            Type::ImplTrait(bounds) => {
+                f.write_str("impl ")?;
+                write_separated(f, bounds, " + ")
+            },
+            Type::Never => f.write_str("!"),
+            Type::Infer => f.write_str("_"),
+            Type::Macro { path, delimiter, tokens } => {
+                let (open, close) = match delimiter {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                };
+                write!(f, "{}!{}", path, open)?;
+                write_tokens(f, tokens)?;
+                f.write_str(close)
+            },
+        }
+    }
+}
+
+impl fmt::Display for TypePath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_global {
+            f.write_str("::")?;
+        }
+        write_separated(f, &self.segments, "::")
+    }
+}
+
+impl fmt::Display for PathSegment<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)?;
+        match &self.arguments {
+            PathArguments::None => Ok(()),
+            PathArguments::AngleBracketed(arguments) => {
+                f.write_str("<")?;
+                write_separated(f, arguments, ", ")?;
+                f.write_str(">")
+            },
+            PathArguments::Parenthesized { inputs, output } => {
+                f.write_str("(")?;
+                write_separated(f, inputs, ", ")?;
+                f.write_str(")")?;
+                if let Some(output) = output {
+                    write!(f, " -> {}", output)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl fmt::Display for GenericArgument<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenericArgument::Lifetime(lifetime) => write!(f, "{}", lifetime),
+            GenericArgument::Type(ty) => write!(f, "{}", ty),
+            GenericArgument::Const(tokens) => write_tokens(f, tokens),
+            GenericArgument::Binding { name, ty } => write!(f, "{} = {}", name, ty),
+            GenericArgument::Constraint { name, bounds } => {
+                write!(f, "{}: ", name)?;
+                write_separated(f, bounds, " + ")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_types {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn parse_and_match_types() {
+        let sources = [
+            "u8",
+            "::std::collections::HashMap<String, Vec<Vec<u8>>>",
+            "&'a mut [&str]",
+            "&&T",
+            "*const *mut u8",
+            "[u8; N * 2]",
+            "(u8, (), (String,))",
+            "for<'a> unsafe extern \"C\" fn(&'a u8, ...) -> !",
+            "Box<dyn Fn(u8) -> u8 + Send + 'static>",
+            "impl Iterator<Item = u8> + ?Sized",
+            "<T as Iterator>::Item",
+            "<Self>::Output",
+            "Foo<'a, 3, { N + 1 }, Item: Clone>",
+            "vec_of!(u8, 3)",
+            "_",
+        ];
+        for source in sources {
+            let ty = Type::parse(source).unwrap_or_else(|| panic!("{}", source));
+            assert_eq!(ty.to_string(), source);
+        }
+        assert_eq!(Type::parse("Vec::<u8>").unwrap().to_string(), "Vec<u8>");
+        assert_eq!(Type::parse("fn(x: u8)").unwrap().to_string(), "fn(u8)");
+        assert_eq!(Type::parse("(u8)"), Type::parse("u8"));
+        assert!(matches!(Type::parse("(u8,)"), Some(Type::Tuple(types)) if types.len() == 1));
+        assert!(Type::parse("Vec<u8").is_none() && Type::parse("u8 u8").is_none());
+
+        let ty = Type::parse("std::vec::Vec<Vec<u8>>").unwrap();
+        assert!(ty.matches_str("Vec<Vec<Primitive>>"));
+        assert!(ty.matches_str("Vec<_>") && ty.matches_str("vec::Vec<_>"));
+        assert!(!ty.matches_str("Vec<Primitive>") && !ty.matches_str("Option<_>") && !ty.matches_str("::Vec<_>"));
+        assert!(Type::parse("Result<(), E>").unwrap().matches_str("Result<(), _>"));
+        assert!(Type::parse("&'a mut [u32]").unwrap().matches_str("&mut [Primitive]"));
+        assert!(!Type::parse("&'a [u32]").unwrap().matches_str("&'b [u32]"));
+        assert!(Type::parse("(u8, String)").unwrap().matches_str("(Primitive, String)"));
+    }
+}
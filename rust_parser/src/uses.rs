@@ -0,0 +1,200 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Keyword, Peekable, Span, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{attributes, Attribute, FromTokenStream, Visibility};
+
+/// `use a::{b::{self, C as D}, e::*};`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Use<'a> {
+    pub visibility: Visibility<'a>,
+    pub attributes: Vec<Attribute<'a>>,
+    /// If the path starts with `::` as in `use ::std::io;`
+    pub is_global: bool,
+    pub tree: UseTree<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UseTree<'a> {
+    /// `name::tree`
+    Path {
+        name: &'a str,
+        tree: Box<UseTree<'a>>,
+    },
+    /// `name` or `name as alias`, the alias can be `_`
+    Name {
+        name: &'a str,
+        alias: Option<&'a str>,
+    },
+    /// `*`
+    Glob,
+    /// `{a, b::c}`
+    Group(Vec<UseTree<'a>>),
+}
+
+/// A single import of a use tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UseEntry<'a> {
+    /// The segments of the path, for a glob the ones before `*`
+    pub path: Vec<&'a str>,
+    pub alias: Option<&'a str>,
+    pub is_glob: bool,
+}
+
+impl<'a> UseEntry<'a> {
+    /// The name the import is known by in the module, `None` for globs and
+    /// `as _` imports
+    pub fn name(&self) -> Option<&'a str> {
+        match self.alias {
+            Some("_") => None,
+            Some(alias) => Some(alias),
+            None if self.is_glob => None,
+            None => self.path.last().copied(),
+        }
+    }
+}
+
+impl<'a> Use<'a> {
+    /// The imports of the tree one by one, `a::{self, b as c, d::*}` gives
+    /// `a`, `a::b` as `c` and the glob `a::d`
+    pub fn flatten(&self) -> Vec<UseEntry<'a>> {
+        let mut entries = Vec::new();
+        self.tree.flatten_into(&mut Vec::new(), &mut entries);
+        entries
+    }
+}
+
+impl<'a> UseTree<'a> {
+    fn flatten_into(&self, prefix: &mut Vec<&'a str>, entries: &mut Vec<UseEntry<'a>>) {
+        match self {
+            UseTree::Path { name, tree } => {
+                prefix.push(name);
+                tree.flatten_into(prefix, entries);
+                prefix.pop();
+            },
+            UseTree::Name { name, alias } => {
+                let mut path = prefix.clone();
+                // `self` in a group is the path of the group
+                if *name != "self" || prefix.is_empty() {
+                    path.push(name);
+                }
+                entries.push(UseEntry { path, alias: *alias, is_glob: false });
+            },
+            UseTree::Glob => entries.push(UseEntry { path: prefix.clone(), alias: None, is_glob: true }),
+            UseTree::Group(trees) => {
+                for tree in trees {
+                    tree.flatten_into(prefix, entries);
+                }
+            },
+        }
+    }
+
+    fn from_tokens(tokens: Vec<SpannedToken<'a>>) -> Option<Self> {
+        let mut token_stream = sub_stream(tokens);
+        let tree = use_tree(&mut token_stream)?;
+        token_stream.peek(0).is_none().then_some(tree)
+    }
+}
+
+fn use_tree<'a>(token_stream: &mut SubStream<'a>) -> Option<UseTree<'a>> {
+    if eat_symbol(token_stream, Symbol::Star).is_some() {
+        return Some(UseTree::Glob);
+    }
+    if let Some((trees, _)) = eat_group(token_stream, Delimiter::Brace) {
+        return split(trees, Symbol::Comma).into_iter()
+            .map(UseTree::from_tokens)
+            .collect::<Option<Vec<_>>>()
+            .map(UseTree::Group);
+    }
+    let name = match *peek_token(token_stream, 0)? {
+        Token::Identifier(identifier) => identifier.name(),
+        Token::Keyword(keyword @ (Keyword::SelfLowercase | Keyword::SelfCapitalized | Keyword::Super | Keyword::Crate)) => keyword.into(),
+        _ => return None,
+    };
+    token_stream.consume(1);
+    if eat_symbol(token_stream, Symbol::PathSep).is_some() {
+        let tree = Box::new(use_tree(token_stream)?);
+        return Some(UseTree::Path { name, tree });
+    }
+    let alias = match eat_keyword(token_stream, Keyword::As) {
+        Some(_) => Some(match *peek_token(token_stream, 0)? {
+            Token::Identifier(identifier) => identifier.name(),
+            Token::Symbol(Symbol::Underscore) => "_",
+            _ => return None,
+        }),
+        None => None,
+    };
+    if alias.is_some() {
+        token_stream.consume(1);
+    }
+    Some(UseTree::Name { name, alias })
+}
+
+impl<'a> FromTokenStream<'a> for Use<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        attempt(token_stream, |token_stream| {
+            let start = token_stream.peek(0)?.span;
+            let attributes = attributes(token_stream);
+            let visibility = Visibility::from_tokens_stream(token_stream)?;
+            eat_keyword(token_stream, Keyword::Use)?;
+            let tokens = take_until(token_stream, |token| *token == Token::Symbol(Symbol::Semi));
+            let end = eat_symbol(token_stream, Symbol::Semi)?;
+
+            let mut tokens = sub_stream(tokens);
+            let is_global = eat_symbol(&mut tokens, Symbol::PathSep).is_some();
+            let tree = UseTree::from_tokens(tokens.collect())?;
+            Some(Use { visibility, attributes, is_global, tree, span: start.join(&end) })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_uses {
+    use super::*;
+    use alloc::string::String;
+    use crate::Module;
+
+    #[test]
+    fn parse_and_flatten_uses() {
+        let source = "
+            use a::{b::{self, C as D}, e::*};
+            #[cfg(test)]
+            pub(crate) use crate::x;
+            use super::y as _;
+            use ::std::io::{};
+            use {f, g::h};
+        ";
+        let module = Module::parse(source).unwrap();
+        let entries = module.uses.iter()
+            .map(|uze| uze.flatten().into_iter()
+                .map(|entry| (entry.path.join("::"), entry.alias, entry.is_glob, entry.name()))
+                .collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(entries, [
+            alloc::vec![
+                (String::from("a::b"), None, false, Some("b")),
+                (String::from("a::b::C"), Some("D"), false, Some("D")),
+                (String::from("a::e"), None, true, None),
+            ],
+            alloc::vec![(String::from("crate::x"), None, false, Some("x"))],
+            alloc::vec![(String::from("super::y"), Some("_"), false, None)],
+            alloc::vec![],
+            alloc::vec![(String::from("f"), None, false, Some("f")), (String::from("g::h"), None, false, Some("h"))],
+        ]);
+
+        assert_eq!(module.uses[1].visibility, Visibility::PublicCrate);
+        assert_eq!(module.uses[1].attributes.len(), 1);
+        assert!(module.uses[3].is_global && !module.uses[0].is_global);
+        assert_eq!(module.uses[3].tree, UseTree::Path {
+            name: "std",
+            tree: Box::new(UseTree::Path { name: "io", tree: Box::new(UseTree::Group(Vec::new())) }),
+        });
+        assert!(source[module.uses[0].span.byte_range()].ends_with("e::*};"));
+    }
+}
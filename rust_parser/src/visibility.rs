@@ -1 +1,248 @@
-use rust_lexer::*;
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use rust_lexer::{Delimiter, Keyword, Peekable, SpannedToken, Symbol, Token};
+use crate::stream::*;
+use crate::{FromTokenStream, Module};
+
+/// How many re-exports are followed to find the item a `pub use` names
+const MAX_REEXPORT_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Visibility<'a> {
+    #[default]
+    Private,
+    Public,
+    PublicCrate,
+    /// `pub(self)`, the same as private
+    PublicSelf,
+    PublicSuper,
+    /// `pub(in some::path)`
+    PublicIn(Vec<&'a str>),
+}
+
+impl<'a> Visibility<'a> {
+    /// If it's `pub`, the only visibility that can reach outside the crate
+    pub fn is_public(&self) -> bool {
+        *self == Visibility::Public
+    }
+}
+
+impl<'a> FromTokenStream<'a> for Visibility<'a> {
+    fn from_tokens_stream<I>(
+        token_stream: &mut Peekable<SpannedToken<'a>, I>
+    ) -> Option<Self>
+    where
+        I: Iterator<Item=SpannedToken<'a>>,
+    {
+        if eat_keyword(token_stream, Keyword::Pub).is_none() {
+            return Some(Visibility::Private);
+        }
+        // the parentheses are not a restriction in `struct S(pub (u8, u8));`
+        let restricted = attempt(token_stream, |token_stream| {
+            let (tokens, _) = eat_group(token_stream, Delimiter::Parenthesis)?;
+            match tokens.as_slice() {
+                [SpannedToken { token: Token::Keyword(Keyword::Crate), .. }] => Some(Visibility::PublicCrate),
+                [SpannedToken { token: Token::Keyword(Keyword::SelfLowercase), .. }] => Some(Visibility::PublicSelf),
+                [SpannedToken { token: Token::Keyword(Keyword::Super), .. }] => Some(Visibility::PublicSuper),
+                [SpannedToken { token: Token::Keyword(Keyword::In), .. }, path @ ..] => {
+                    split(path.to_vec(), Symbol::PathSep).into_iter()
+                        .map(|segment| match segment.as_slice() {
+                            [SpannedToken { token: Token::Identifier(identifier), .. }] => Some(identifier.name()),
+                            [SpannedToken { token: Token::Keyword(keyword @ (Keyword::Crate | Keyword::SelfLowercase | Keyword::Super)), .. }] => {
+                                Some((*keyword).into())
+                            },
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .filter(|path| !path.is_empty())
+                        .map(Visibility::PublicIn)
+                },
+                _ => None,
+            }
+        });
+        Some(restricted.unwrap_or(Visibility::Public))
+    }
+}
+
+/// What a path names, found by [`resolve`]
+enum Target {
+    Module(Vec<String>),
+    Item(Vec<String>),
+}
+
+/// The paths from the crate root of the items that can be named from outside
+/// of the crate, as `["graph", "Graph"]`. An item is reachable if it's `pub`
+/// in a reachable module or if a `pub use` of a reachable module re-exports
+/// it, the root module always being reachable. The paths of re-exports to
+/// other crates can't be resolved and are skipped.
+pub fn reachable_items(root: &Module<'_>) -> BTreeSet<Vec<String>> {
+    let mut items = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut stack = alloc::vec![Vec::new()];
+    while let Some(module_path) = stack.pop() {
+        if !visited.insert(module_path.clone()) {
+            continue;
+        }
+        let Some(module) = module_at(root, &module_path) else {
+            continue;
+        };
+        for (name, visibility) in module_items(module) {
+            if !visibility.is_public() {
+                continue;
+            }
+            let mut path = module_path.clone();
+            path.push(name.to_string());
+            if module.mods.contains_key(name) {
+                stack.push(path);
+            } else {
+                items.insert(path);
+            }
+        }
+        for entry in module.uses.iter().filter(|uze| uze.visibility.is_public()).flat_map(|uze| uze.flatten()) {
+            match resolve(root, &module_path, &entry.path, 0) {
+                // a glob re-exports all the public items, as if the module was reachable
+                Some(Target::Module(path)) if entry.is_glob => stack.push(path),
+                Some(Target::Module(path)) if entry.name().is_some() => stack.push(path),
+                Some(Target::Item(path)) if !entry.is_glob && entry.name().is_some() => {
+                    items.insert(path);
+                },
+                _ => {},
+            }
+        }
+    }
+    items
+}
+
+fn module_at<'m, 'a>(root: &'m Module<'a>, path: &[String]) -> Option<&'m Module<'a>> {
+    path.iter().try_fold(root, |module, name| module.mods.get(name))
+}
+
+/// The names defined in the module with their visibility
+fn module_items<'m>(module: &'m Module<'_>) -> Vec<(&'m str, &'m Visibility<'m>)> {
+    let mut items = Vec::new();
+    items.extend(module.structs.iter().map(|item| (item.name.name(), &item.visibility)));
+    items.extend(module.enums.iter().map(|item| (item.name.name(), &item.visibility)));
+    items.extend(module.unions.iter().map(|item| (item.name.name(), &item.visibility)));
+    items.extend(module.traits.iter().map(|item| (item.name.name(), &item.visibility)));
+    items.extend(module.functions.iter().map(|item| (item.name.name(), &item.visibility)));
+    items.extend(module.mods.iter().map(|(name, module)| (name.as_str(), &module.visibility)));
+    items
+}
+
+/// Find the public module or item that the path of a use in the module names,
+/// following the re-exports
+fn resolve(root: &Module<'_>, module_path: &[String], path: &[&str], depth: usize) -> Option<Target> {
+    if depth > MAX_REEXPORT_DEPTH {
+        return None;
+    }
+    let (first, rest) = path.split_first()?;
+    let mut current = match *first {
+        "crate" => Vec::new(),
+        "self" => module_path.to_vec(),
+        "super" => module_path.get(..module_path.len().checked_sub(1)?)?.to_vec(),
+        // a path starts in the module, or at the root in the 2015 edition
+        name if module_at(root, module_path)?.mods.contains_key(name) => module_path.to_vec(),
+        _ => Vec::new(),
+    };
+    let segments = if matches!(*first, "crate" | "self" | "super") { rest } else { path };
+
+    for (index, name) in segments.iter().enumerate() {
+        let module = module_at(root, &current)?;
+        if *name == "super" {
+            current.pop();
+            continue;
+        }
+        let is_last = index + 1 == segments.len();
+        if let Some((_, visibility)) = module_items(module).into_iter().find(|(item, _)| item == name) {
+            if !is_visible(visibility, module_path, &current) {
+                return None;
+            }
+            current.push(name.to_string());
+            if module.mods.contains_key(*name) {
+                continue;
+            }
+            return (is_last && visibility.is_public()).then_some(Target::Item(current));
+        }
+        // the name can come from a re-export of the module, named or else
+        // through a glob
+        let entries = module.uses.iter()
+            .filter(|uze| is_visible(&uze.visibility, module_path, &current))
+            .flat_map(|uze| uze.flatten())
+            .collect::<Vec<_>>();
+        if let Some(entry) = entries.iter().find(|entry| !entry.is_glob && entry.name() == Some(*name)) {
+            let mut reexported = entry.path.clone();
+            reexported.extend(&segments[index + 1..]);
+            return resolve(root, &current, &reexported, depth + 1);
+        }
+        return entries.iter().filter(|entry| entry.is_glob).find_map(|entry| {
+            let mut reexported = entry.path.clone();
+            reexported.extend(&segments[index..]);
+            resolve(root, &current, &reexported, depth + 1)
+        });
+    }
+    Some(Target::Module(current))
+}
+
+/// The private items and uses are only visible in their module and its
+/// children
+fn is_visible(visibility: &Visibility<'_>, module_path: &[String], current: &[String]) -> bool {
+    !matches!(visibility, Visibility::Private | Visibility::PublicSelf) || module_path.starts_with(current)
+}
+
+#[cfg(test)]
+mod test_visibility {
+    use super::*;
+
+    #[test]
+    fn parse_visibilities_and_reachability() {
+        let source = "
+            pub fn a() {}
+            pub(crate) fn b() {}
+            pub(self) fn c() {}
+            pub(super) fn d() {}
+            pub(in crate::graph) fn e() {}
+            fn f() {}
+            pub struct Tuple(pub (u8, u8), pub(crate) u8);
+        ";
+        let module = Module::parse(source).unwrap();
+        let visibilities = module.functions.iter().map(|function| function.visibility.clone()).collect::<Vec<_>>();
+        assert_eq!(visibilities, [
+            Visibility::Public,
+            Visibility::PublicCrate,
+            Visibility::PublicSelf,
+            Visibility::PublicSuper,
+            Visibility::PublicIn(alloc::vec!["crate", "graph"]),
+            Visibility::Private,
+        ]);
+        let fields = module.structs[0].fields.iter().map(|field| field.visibility.clone()).collect::<Vec<_>>();
+        assert_eq!(fields, [Visibility::Public, Visibility::PublicCrate]);
+
+        // crate {
+        //     pub use graph::Graph; pub use utils::*; pub use prelude::Hidden; pub use private::Renamed;
+        //     pub mod api; mod graph; mod utils; mod prelude; mod private; mod shapes;
+        // }
+        let mut root = Module::parse("
+            pub use graph::{Graph, Internal}; pub use utils::*; pub use prelude::Hidden; pub use private::Renamed;
+            pub fn main() {} fn hidden() {}
+        ").unwrap();
+        let graph = Module::parse("pub struct Graph; pub struct Hidden; pub(crate) struct Internal;").unwrap();
+        let utils = Module::parse("pub fn helper() {} pub(crate) fn internal() {} pub use self::nested::Deep;").unwrap();
+        let nested = Module::parse("pub struct Deep; pub struct NotExported;").unwrap();
+        let mut api = Module::parse("pub use crate::graph::Graph as Alias; pub enum Kind { A }").unwrap();
+        api.visibility = Visibility::Public;
+        let mut utils = utils;
+        utils.mods.insert("nested".into(), nested);
+        root.mods.insert("graph".into(), graph);
+        root.mods.insert("utils".into(), utils);
+        root.mods.insert("api".into(), api);
+        // the glob of the prelude re-exports `Hidden`, but the private use
+        // doesn't make `Circle` reachable
+        root.mods.insert("prelude".into(), Module::parse("pub use crate::graph::*;").unwrap());
+        root.mods.insert("private".into(), Module::parse("use crate::shapes::Circle as Renamed;").unwrap());
+        root.mods.insert("shapes".into(), Module::parse("pub struct Circle;").unwrap());
+
+        let reachable = reachable_items(&root).into_iter().map(|path| path.join("::")).collect::<Vec<_>>();
+        assert_eq!(reachable, ["api::Kind", "graph::Graph", "graph::Hidden", "main", "utils::helper", "utils::nested::Deep"]);
+    }
+}